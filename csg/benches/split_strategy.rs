@@ -0,0 +1,74 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compares the BSP splitting strategies on the cup from the cup_csg example.
+//!
+//! Run with `cargo bench --bench split_strategy`.
+
+use csg::{BSPNode, CsgOptions, SplitStrategy, CSG};
+use std::time::Instant;
+
+// the cup of the cup_csg example, its main is not used here
+#[path = "../examples/cup_csg.rs"]
+#[allow(dead_code)]
+mod cup_csg;
+
+fn main() {
+  let (cup_blank, handle, cup_inner) = cup_csg::cup_parts();
+  let strategies = [
+    ("first", SplitStrategy::First),
+    ("balanced", SplitStrategy::default()),
+  ];
+  println!(
+    "{:<10} {:>14} {:>14} {:>12} {:>12}",
+    "strategy", "tree polygons", "result tris", "build", "cup"
+  );
+  for (name, split_strategy) in strategies {
+    let options = CsgOptions {
+      split_strategy,
+      ..Default::default()
+    };
+
+    // The number of polygons in the trees shows how many fragments splitting made.
+    let start = Instant::now();
+    let mut tree_polygons = 0;
+    for part in [&cup_blank, &handle, &cup_inner] {
+      let polygons = CSG::from_mesh(part.clone()).polygons;
+      tree_polygons += BSPNode::with_options(polygons, &options)
+        .all_polygons()
+        .len();
+    }
+    let build = start.elapsed();
+
+    let start = Instant::now();
+    let cup = CSG::from_mesh(cup_blank.clone())
+      .union_with(CSG::from_mesh(handle.clone()), &options)
+      .subtract_with(CSG::from_mesh(cup_inner.clone()), &options);
+    let result_triangles = cup.into_triangles().len();
+    let elapsed = start.elapsed();
+
+    println!(
+      "{:<10} {:>14} {:>14} {:>12.3?} {:>12.3?}",
+      name, tree_polygons, result_triangles, build, elapsed
+    );
+  }
+}
@@ -0,0 +1,57 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Times welding the vertices of spheres of growing size, the time per triangle
+//! should stay about the same since the spatial hash makes welding near linear.
+//!
+//! Run with `cargo bench --bench weld`.
+
+use csg::{IndexedMesh, Mesh};
+use std::time::Instant;
+
+fn main() {
+  println!(
+    "{:>10} {:>10} {:>12} {:>12} {:>14}",
+    "triangles", "vertices", "exact", "welded", "welded / tri"
+  );
+  for segments in [64, 128, 256, 512, 1024] {
+    let mesh = Mesh::sphere(10.0, segments);
+
+    let start = Instant::now();
+    let exact = IndexedMesh::from_mesh(&mesh);
+    let exact_time = start.elapsed();
+
+    let start = Instant::now();
+    let welded = IndexedMesh::from_triangles_welded(&mesh.triangles, 1.0e-6);
+    let welded_time = start.elapsed();
+    assert_eq!(welded.vertices.len(), exact.vertices.len());
+
+    println!(
+      "{:>10} {:>10} {:>12.3?} {:>12.3?} {:>14.3?}",
+      mesh.triangles.len(),
+      welded.vertices.len(),
+      exact_time,
+      welded_time,
+      welded_time / mesh.triangles.len() as u32
+    );
+  }
+}
@@ -0,0 +1,49 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This example saves a small colored assembly as a 3mf file that can be opened in a
+//! slicer.
+
+use csg::{Assembly, Mesh, Mt4, Part, Unit};
+
+fn main() {
+  let mut assembly = Assembly::new(Unit::Millimeter);
+
+  let mut base = Part::new("base", Mesh::cube(40.0, 40.0, 4.0, false));
+  base.color = Some([200, 200, 200, 255]);
+  assembly.add(base);
+
+  for (i, color) in [[220, 40, 40, 255], [40, 160, 40, 255], [40, 80, 220, 255]]
+    .into_iter()
+    .enumerate()
+  {
+    let mut peg = Part::new(
+      &format!("peg {}", i + 1),
+      Mesh::cylinder(3.0, 3.0, 10.0, 24, false),
+    );
+    peg.color = Some(color);
+    peg.transform = Mt4::translate_matrix(10.0 + 10.0 * i as f64, 20.0, 4.0);
+    assembly.add(peg);
+  }
+
+  assembly.save_3mf("out/assembly.3mf").unwrap();
+}
@@ -0,0 +1,45 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This example cuts a square notch into a sphere of about 200,000 triangles on the
+//! main thread with the default stack size.
+
+use {
+  csg::{Mesh, Pt3},
+  std::time::Instant,
+};
+
+fn main() {
+  let sphere = Mesh::sphere(50.0, 450);
+  let mut cube = Mesh::cube(60.0, 60.0, 60.0, true);
+  cube.translate(Pt3::new(30.0, 0.0, 0.0));
+
+  let start = Instant::now();
+  let notched = sphere.clone() - cube;
+  println!(
+    "{} triangles minus a cube gives {} triangles in {:.3?}",
+    sphere.triangles.len(),
+    notched.triangles.len(),
+    start.elapsed()
+  );
+  notched.save_stl_bin("out/big_boolean.stl").unwrap();
+}
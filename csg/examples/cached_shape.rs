@@ -0,0 +1,62 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This example builds a parametric plate with a cache. Run it twice and the second
+//! run loads the booleans from out/cache. Pass a different hole count as the first
+//! argument and only the parts that changed are computed again.
+
+use {
+  csg::{Cache, CsgOptions, Mesh, Pt3, Shape},
+  std::time::Instant,
+};
+
+fn main() {
+  let holes: usize = std::env::args()
+    .nth(1)
+    .and_then(|arg| arg.parse().ok())
+    .unwrap_or(6);
+
+  // the body doesn't depend on the number of holes so it stays in the cache
+  let mut body = Shape::mesh(Mesh::cube(60.0, 60.0, 8.0, true));
+  for (x, y) in [(-25.0, -25.0), (25.0, -25.0), (25.0, 25.0), (-25.0, 25.0)] {
+    let mut boss = Shape::mesh(Mesh::cylinder(6.0, 6.0, 12.0, 48, false));
+    boss.translate(Pt3::new(x, y, 0.0));
+    body += boss;
+  }
+  body += Shape::mesh(Mesh::sphere(20.0, 64));
+
+  let mut plate = body;
+  for i in 0..holes {
+    let mut hole = Shape::mesh(Mesh::cylinder(2.5, 2.5, 40.0, 32, true));
+    hole.translate(Pt3::new(-20.0 + 40.0 * i as f64 / holes as f64, 0.0, 0.0));
+    plate -= hole;
+  }
+
+  let start = Instant::now();
+  let mesh = plate.evaluate_cached(&CsgOptions::default(), &Cache::new("out/cache"));
+  println!(
+    "{} triangles in {:.3?}",
+    mesh.triangles.len(),
+    start.elapsed()
+  );
+  mesh.save_stl_bin("out/cached_shape.stl").unwrap();
+}
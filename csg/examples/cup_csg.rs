@@ -25,55 +25,56 @@
 use csg::{Mesh, Pt2, Pt3};
 
 fn main() {
-  // We need to start another thread or we will blow the stack.
-  let worker_thread = std::thread::Builder::new()
-    .stack_size(32 * 1024 * 1024)
-    .spawn(move || {
-      let segments: usize = 36;
-      let slices: usize = 12;
+  let (cup_blank, handle, cup_inner) = cup_parts();
+  let cup = cup_blank + handle - cup_inner;
 
-      let mut cup_blank_profile = Vec::new();
-      cup_blank_profile.push(Pt2::new(0.0, 0.0));
-      cup_blank_profile.append(&mut Pt2::cubic_bezier(
-        Pt2::new(40.0, 0.0),
-        Pt2::new(40.0, 33.0),
-        Pt2::new(60.0, 66.0),
-        Pt2::new(60.0, 100.0),
-        slices,
-      ));
-      cup_blank_profile.push(Pt2::new(0.0, 100.0));
+  cup.save_stl_bin("out/cup.stl").unwrap();
+}
+
+/// The meshes the cup is made of, also used by the split_strategy benchmark.
+///
+/// return: The cup blank, the handle and the inside of the cup.
+pub fn cup_parts() -> (Mesh, Mesh, Mesh) {
+  let segments: usize = 36;
+  let slices: usize = 12;
 
-      let cup_blank = Mesh::revolve(&cup_blank_profile, segments);
+  let mut cup_blank_profile = Vec::new();
+  cup_blank_profile.push(Pt2::new(0.0, 0.0));
+  cup_blank_profile.append(&mut Pt2::cubic_bezier(
+    Pt2::new(40.0, 0.0),
+    Pt2::new(40.0, 33.0),
+    Pt2::new(60.0, 66.0),
+    Pt2::new(60.0, 100.0),
+    slices,
+  ));
+  cup_blank_profile.push(Pt2::new(0.0, 100.0));
 
-      let mut cup_inner_profile = Vec::new();
-      cup_inner_profile.push(Pt2::new(0.0, 3.0));
-      cup_inner_profile.append(&mut Pt2::cubic_bezier(
-        Pt2::new(37.0, 3.0),
-        Pt2::new(37.0, 33.0),
-        Pt2::new(57.0, 66.0),
-        Pt2::new(57.0, 103.0),
-        slices,
-      ));
-      cup_inner_profile.push(Pt2::new(0.0, 103.0));
+  let cup_blank = Mesh::revolve(&cup_blank_profile, segments);
 
-      let cup_inner = Mesh::revolve(&cup_inner_profile, segments);
+  let mut cup_inner_profile = Vec::new();
+  cup_inner_profile.push(Pt2::new(0.0, 3.0));
+  cup_inner_profile.append(&mut Pt2::cubic_bezier(
+    Pt2::new(37.0, 3.0),
+    Pt2::new(37.0, 33.0),
+    Pt2::new(57.0, 66.0),
+    Pt2::new(57.0, 103.0),
+    slices,
+  ));
+  cup_inner_profile.push(Pt2::new(0.0, 103.0));
 
-      let handle_path = Pt3::cubic_bezier(
-        Pt3::new(37.0, 20.0, 0.0),
-        Pt3::new(70.0, 30.0, 0.0),
-        Pt3::new(120.0, 90.0, 0.0),
-        Pt3::new(57.0, 90.0, 0.0),
-        segments,
-      );
+  let cup_inner = Mesh::revolve(&cup_inner_profile, segments);
 
-      let handle_profile = Pt2::rounded_rect(8.0, 20.0, 2.5, segments, true);
-      let mut handle = Mesh::sweep(&handle_profile, &handle_path, 0.0);
-      handle.rotate_x(90.0);
+  let handle_path = Pt3::cubic_bezier(
+    Pt3::new(37.0, 20.0, 0.0),
+    Pt3::new(70.0, 30.0, 0.0),
+    Pt3::new(120.0, 90.0, 0.0),
+    Pt3::new(57.0, 90.0, 0.0),
+    segments,
+  );
 
-      let cup = cup_blank + handle - cup_inner;
+  let handle_profile = Pt2::rounded_rect(8.0, 20.0, 2.5, segments, true);
+  let mut handle = Mesh::sweep(&handle_profile, &handle_path, 0.0);
+  handle.rotate_x(90.0);
 
-      cup.save_stl_bin("out/cup.stl");
-    })
-    .unwrap();
-  worker_thread.join().unwrap();
+  (cup_blank, handle, cup_inner)
 }
@@ -26,11 +26,14 @@
 use csg::{Mesh, Viewer};
 
 fn main() {
-  let suzanne = Mesh::load_stl("in/suzanne.stl");
+  let suzanne = Mesh::load_stl("in/suzanne.stl").unwrap();
   let mut viewer = Viewer::new(0.1, 0.05, 12);
 
   viewer.add_pt3s(suzanne.vertices());
   viewer.add_edges(suzanne.edges());
 
-  viewer.render().save_stl_bin("out/SuzanneViewer.stl");
+  viewer
+    .render()
+    .save_stl_bin("out/SuzanneViewer.stl")
+    .unwrap();
 }
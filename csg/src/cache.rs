@@ -0,0 +1,284 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! An on-disk cache for the results of boolean operations.
+//!
+//! Results are stored in files named after a hash of everything that went into
+//! them, the geometry of the operands, the operation and the options, so an
+//! unchanged part of a model loads from the cache on the next run instead of being
+//! computed again. The hash does not depend on the platform or the Rust version.
+
+use {
+  crate::{
+    weld::float_key, CsgOptions, Plane, Polygon, Precision, Pt3, SplitStrategy, Tolerance, CSG,
+  },
+  std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+  },
+};
+
+/// The first bytes of a cache file, bumped when the format changes.
+const MAGIC: &[u8; 8] = b"CSGCACH3";
+
+/// The version of the boolean operations that is part of every key. Bump it whenever a
+/// change to the boolean operations changes their results, so results of the old
+/// code are not loaded. The crate version alone is not enough since it doesn't change
+/// between releases.
+const ALGORITHM_VERSION: u64 = 3;
+
+/// A directory of cached boolean results.
+#[derive(Clone, Debug)]
+pub struct Cache {
+  dir: PathBuf,
+}
+
+impl Cache {
+  /// Create a cache that keeps its files in a directory. The directory is created
+  /// when the first result is stored.
+  ///
+  /// dir: The directory of the cache.
+  ///
+  /// return: The cache.
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self { dir: dir.into() }
+  }
+
+  /// Load a result.
+  ///
+  /// key: The hash of the operation.
+  ///
+  /// return: The result or None if it is not in the cache or can't be read.
+  pub(crate) fn load(&self, key: u128) -> Option<CSG> {
+    let mut file = fs::File::open(self.path(key)).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    decode(&bytes)
+  }
+
+  /// Store a result. A cache that can't be written to is the same as an empty
+  /// cache so errors are ignored.
+  ///
+  /// key: The hash of the operation.
+  ///
+  /// csg: The result.
+  pub(crate) fn store(&self, key: u128, csg: &CSG) {
+    let _ = self.try_store(key, csg);
+  }
+
+  fn try_store(&self, key: u128, csg: &CSG) -> io::Result<()> {
+    fs::create_dir_all(&self.dir)?;
+    // write to a temporary file first so a crash never leaves half a result
+    let tmp = self
+      .dir
+      .join(format!("{:032x}.{}.tmp", key, std::process::id()));
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&encode(csg))?;
+    file.sync_all()?;
+    fs::rename(tmp, self.path(key))
+  }
+
+  fn path(&self, key: u128) -> PathBuf {
+    self.dir.join(format!("{:032x}.csg", key))
+  }
+}
+
+/// A 128 bit FNV-1a hash, used for cache keys because it is the same everywhere.
+pub(crate) struct StableHasher {
+  state: u128,
+}
+
+impl StableHasher {
+  const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+  const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+  /// Start a hash with the crate version, the cache format and the version of the
+  /// boolean operations, so results from other versions are not used.
+  pub(crate) fn new() -> Self {
+    let mut hasher = Self {
+      state: Self::OFFSET,
+    };
+    hasher.bytes(MAGIC);
+    hasher.bytes(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.u64(ALGORITHM_VERSION);
+    hasher
+  }
+
+  pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.state ^= *byte as u128;
+      self.state = self.state.wrapping_mul(Self::PRIME);
+    }
+  }
+
+  pub(crate) fn u64(&mut self, value: u64) {
+    self.bytes(&value.to_le_bytes());
+  }
+
+  pub(crate) fn u128(&mut self, value: u128) {
+    self.bytes(&value.to_le_bytes());
+  }
+
+  pub(crate) fn f64(&mut self, value: f64) {
+    self.u64(float_key(value));
+  }
+
+  pub(crate) fn pt3(&mut self, p: Pt3) {
+    self.f64(p.x);
+    self.f64(p.y);
+    self.f64(p.z);
+  }
+
+  pub(crate) fn options(&mut self, options: &CsgOptions) {
+    self.tolerance(options.tolerance);
+    match options.snap_grid {
+      Some(grid) => {
+        self.u64(1);
+        self.f64(grid);
+      }
+      None => self.u64(0),
+    }
+    self.tolerance(options.merge_distance);
+    self.u64(match options.precision {
+      Precision::Epsilon => 0,
+      Precision::Exact => 1,
+    });
+    match options.split_strategy {
+      SplitStrategy::First => self.u64(0),
+      SplitStrategy::Balanced {
+        candidates,
+        split_weight,
+      } => {
+        self.u64(1);
+        self.u64(candidates as u64);
+        self.f64(split_weight);
+      }
+    }
+  }
+
+  fn tolerance(&mut self, tolerance: Tolerance) {
+    match tolerance {
+      Tolerance::Absolute(distance) => {
+        self.u64(0);
+        self.f64(distance);
+      }
+      Tolerance::Relative(fraction) => {
+        self.u64(1);
+        self.f64(fraction);
+      }
+    }
+  }
+
+  pub(crate) fn finish(&self) -> u128 {
+    self.state
+  }
+}
+
+/// Write polygons with their planes so they load exactly as they were.
+fn encode(csg: &CSG) -> Vec<u8> {
+  let mut bytes = Vec::new();
+  bytes.extend_from_slice(MAGIC);
+  bytes.extend_from_slice(&(csg.polygons.len() as u64).to_le_bytes());
+  let pt3 = |bytes: &mut Vec<u8>, p: Pt3| {
+    for v in [p.x, p.y, p.z] {
+      bytes.extend_from_slice(&v.to_le_bytes());
+    }
+  };
+  for polygon in &csg.polygons {
+    pt3(&mut bytes, polygon.plane.normal);
+    bytes.extend_from_slice(&polygon.plane.w.to_le_bytes());
+    match polygon.plane.points {
+      Some(points) => {
+        bytes.push(1);
+        for p in points {
+          pt3(&mut bytes, p);
+        }
+      }
+      None => bytes.push(0),
+    }
+    bytes.extend_from_slice(&(polygon.vertices.len() as u64).to_le_bytes());
+    for v in &polygon.vertices {
+      pt3(&mut bytes, *v);
+    }
+  }
+  bytes
+}
+
+/// Read what encode wrote.
+///
+/// return: The polygons or None if the bytes are not a complete cache file.
+fn decode(bytes: &[u8]) -> Option<CSG> {
+  let mut reader = Reader { bytes };
+  if reader.take(MAGIC.len())? != MAGIC {
+    return None;
+  }
+  let n_polygons = reader.u64()?;
+  let mut polygons = Vec::new();
+  for _ in 0..n_polygons {
+    let normal = reader.pt3()?;
+    let w = reader.f64()?;
+    let points = match reader.take(1)?[0] {
+      0 => None,
+      1 => Some([reader.pt3()?, reader.pt3()?, reader.pt3()?]),
+      _ => return None,
+    };
+    let n_vertices = reader.u64()?;
+    // a corrupt count fails on the first missing vertex instead of allocating
+    let mut vertices = Vec::new();
+    for _ in 0..n_vertices {
+      vertices.push(reader.pt3()?);
+    }
+    polygons.push(Polygon::with_plane(vertices, Plane { normal, w, points }));
+  }
+  if !reader.bytes.is_empty() {
+    return None;
+  }
+  Some(CSG { polygons })
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+    if self.bytes.len() < n {
+      return None;
+    }
+    let (taken, rest) = self.bytes.split_at(n);
+    self.bytes = rest;
+    Some(taken)
+  }
+
+  fn u64(&mut self) -> Option<u64> {
+    Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+  }
+
+  fn f64(&mut self) -> Option<f64> {
+    Some(f64::from_le_bytes(self.take(8)?.try_into().ok()?))
+  }
+
+  fn pt3(&mut self) -> Option<Pt3> {
+    Some(Pt3::new(self.f64()?, self.f64()?, self.f64()?))
+  }
+}
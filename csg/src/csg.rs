@@ -23,7 +23,12 @@
 
 //! Constructive Solid Geometry part of https://github.com/timknip/pycsg port
 
-use crate::{Mesh, Pt3, Triangle};
+use crate::{
+  merge::merge_coplanar,
+  orient3d,
+  weld::{vertex_key, Welder},
+  Aabb, Mesh, Mt4, Pt3, Triangle,
+};
 
 #[derive(Clone, Default)]
 pub struct CSG {
@@ -42,6 +47,10 @@ impl CSG {
   pub fn from_triangles(triangles: Vec<Triangle>) -> Self {
     let mut polygons: Vec<Polygon> = Vec::with_capacity(triangles.len());
     for triangle in triangles {
+      // degenerate triangles have no plane and would poison the BSP tree
+      if triangle.normal().len2() == 0.0 {
+        continue;
+      }
       let vertices = vec![triangle.a, triangle.b, triangle.c];
       let polygon = Polygon::new(vertices);
       polygons.push(polygon);
@@ -52,12 +61,33 @@ impl CSG {
   pub fn into_triangles(self) -> Vec<Triangle> {
     let mut triangles: Vec<Triangle> = Vec::new();
     for polygon in self.polygons {
-      for i in 1..(polygon.vertices.len() - 1) {
-        triangles.push(Triangle::new(
-          polygon.vertices[0],
-          polygon.vertices[i],
-          polygon.vertices[i + 1],
-        ));
+      let vertices = &polygon.vertices;
+      let n = vertices.len();
+      if n < 3 {
+        continue;
+      }
+      match fan_apex(vertices, polygon.plane.normal) {
+        Some(apex) => {
+          for i in 1..(n - 1) {
+            triangles.push(Triangle::new(
+              vertices[apex],
+              vertices[(apex + i) % n],
+              vertices[(apex + i + 1) % n],
+            ));
+          }
+        }
+        None => {
+          // Every fan has a zero area triangle because of vertices inserted on the
+          // edges, so fan from the middle of the polygon instead.
+          let mut center = Pt3::new(0.0, 0.0, 0.0);
+          for v in vertices {
+            center += *v;
+          }
+          center /= n as f64;
+          for i in 0..n {
+            triangles.push(Triangle::new(center, vertices[i], vertices[(i + 1) % n]));
+          }
+        }
       }
     }
     triangles
@@ -109,26 +139,109 @@ impl CSG {
     new_csg
   }
 
+  /// The axis aligned box around the polygons.
+  ///
+  /// return: The box or None if there are no polygons.
+  pub fn bounding_box(&self) -> Option<Aabb> {
+    bounds_of(self.polygons.iter())
+  }
+
   pub fn translate(&mut self, displacement: Pt3) {
+    self.transform(&Mt4::translate_matrix(
+      displacement.x,
+      displacement.y,
+      displacement.z,
+    ));
+  }
+
+  /// Transform the polygons and their planes by an affine matrix. The winding is
+  /// reversed if the matrix mirrors so the solid stays outward facing.
+  pub fn transform(&mut self, matrix: &Mt4) {
+    let flip = matrix.determinant() < 0.0;
     for poly in &mut self.polygons {
-      for v in &mut poly.vertices {
-        *v += displacement;
-      }
+      poly.transform_winding(matrix, flip);
     }
   }
 
+  /// Scale about the origin, negative factors mirror.
+  pub fn scale(&mut self, factors: Pt3) {
+    self.transform(&Mt4::scale_matrix(factors.x, factors.y, factors.z));
+  }
+
+  pub fn scale_uniform(&mut self, factor: f64) {
+    self.scale(Pt3::new(factor, factor, factor));
+  }
+
+  /// Mirror across the plane with the given normal through point.
+  pub fn mirror(&mut self, normal: Pt3, point: Pt3) {
+    self.transform(&Mt4::mirror_matrix(normal, point));
+  }
+
+  /// Rotate around the axis through pivot, counter clockwise looking down the axis.
+  pub fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) {
+    self.transform(&Mt4::rot_axis_matrix(axis, pivot, degrees));
+  }
+
   pub fn union(&self, csg: CSG) -> CSG {
-    let mut a = Box::new(BSPNode::new(Some(self.clone().polygons)));
-    let mut b = Box::new(BSPNode::new(Some(csg.clone().polygons)));
+    self.union_with(csg, &CsgOptions::default())
+  }
+
+  /// Union using exact orientation tests to classify vertices. See [`Precision::Exact`].
+  pub fn union_exact(&self, csg: CSG) -> CSG {
+    self.union_with(csg, &CsgOptions::exact())
+  }
+
+  /// Union with the given tolerances.
+  pub fn union_with(&self, csg: CSG, options: &CsgOptions) -> CSG {
+    options.assert_valid();
+    self.clone().union_into(csg, options)
+  }
+
+  fn union_into(self, csg: CSG, options: &CsgOptions) -> CSG {
+    let Operands {
+      mut a,
+      mut b,
+      mut a_outside,
+      mut b_outside,
+      merge_distance,
+    } = Operands::new(self, csg, options);
     a.clip_to(&mut b);
     b.clip_to(&mut a);
     b.invert();
     b.clip_to(&mut a);
     b.invert();
     a.build(b.all_polygons());
-    CSG {
-      polygons: a.all_polygons(),
-    }
+    let mut polygons = a.all_polygons();
+    polygons.append(&mut a_outside);
+    polygons.append(&mut b_outside);
+    Self::from_result(polygons, merge_distance)
+  }
+
+  /// Merge coplanar polygons that share edges into larger regions and triangulate
+  /// them again. The BSP tree cuts flat faces into many fragments, this puts them
+  /// back together so the mesh has far fewer triangles and slivers.
+  pub fn merge_coplanar(&mut self) {
+    self.polygons = merge_coplanar(std::mem::take(&mut self.polygons));
+  }
+
+  /// Weld vertices that are within tolerance of each other, remove polygons with no
+  /// area and split edges at T-junctions. Boolean operations already do this to their
+  /// results, this is for polygons that come from somewhere else like a loaded STL.
+  ///
+  /// tolerance: How close vertices have to be to weld and how thin a polygon has to
+  /// be to remove. Must be greater than zero.
+  pub fn clean(&mut self, tolerance: f64) {
+    assert!(tolerance > 0.0);
+    weld_vertices(&mut self.polygons, tolerance);
+    remove_slivers(&mut self.polygons, tolerance);
+    fix_t_junctions(&mut self.polygons, tolerance);
+  }
+
+  /// Weld and close up the polygons that come out of a boolean operation.
+  fn from_result(mut polygons: Vec<Polygon>, merge_distance: f64) -> Self {
+    weld_vertices(&mut polygons, merge_distance);
+    fix_t_junctions(&mut polygons, merge_distance);
+    CSG { polygons }
   }
 }
 
@@ -136,14 +249,35 @@ impl std::ops::Add<CSG> for CSG {
   type Output = Self;
 
   fn add(self, rhs: CSG) -> Self::Output {
-    self.union(rhs)
+    self.union_into(rhs, &CsgOptions::default())
   }
 }
 
 impl CSG {
   pub fn subtract(&self, csg: CSG) -> Self {
-    let mut a = Box::new(BSPNode::new(Some(self.clone().polygons)));
-    let mut b = Box::new(BSPNode::new(Some(csg.clone().polygons)));
+    self.subtract_with(csg, &CsgOptions::default())
+  }
+
+  /// Difference using exact orientation tests to classify vertices. See [`Precision::Exact`].
+  pub fn subtract_exact(&self, csg: CSG) -> Self {
+    self.subtract_with(csg, &CsgOptions::exact())
+  }
+
+  /// Difference with the given tolerances.
+  pub fn subtract_with(&self, csg: CSG, options: &CsgOptions) -> Self {
+    options.assert_valid();
+    self.clone().subtract_into(csg, options)
+  }
+
+  fn subtract_into(self, csg: CSG, options: &CsgOptions) -> Self {
+    // the parts of b outside the bounding box of a can't cut anything away
+    let Operands {
+      mut a,
+      mut b,
+      mut a_outside,
+      merge_distance,
+      ..
+    } = Operands::new(self, csg, options);
     a.invert();
     a.clip_to(&mut b);
     b.clip_to(&mut a);
@@ -152,9 +286,9 @@ impl CSG {
     b.invert();
     a.build(b.all_polygons());
     a.invert();
-    CSG {
-      polygons: a.all_polygons(),
-    }
+    let mut polygons = a.all_polygons();
+    polygons.append(&mut a_outside);
+    Self::from_result(polygons, merge_distance)
   }
 }
 
@@ -162,14 +296,34 @@ impl std::ops::Sub<CSG> for CSG {
   type Output = CSG;
 
   fn sub(self, rhs: CSG) -> Self::Output {
-    self.subtract(rhs)
+    self.subtract_into(rhs, &CsgOptions::default())
   }
 }
 
 impl CSG {
   pub fn intersect(&self, csg: CSG) -> Self {
-    let mut a = Box::new(BSPNode::new(Some(self.clone().polygons)));
-    let mut b = Box::new(BSPNode::new(Some(csg.clone().polygons)));
+    self.intersect_with(csg, &CsgOptions::default())
+  }
+
+  /// Intersection using exact orientation tests to classify vertices. See [`Precision::Exact`].
+  pub fn intersect_exact(&self, csg: CSG) -> Self {
+    self.intersect_with(csg, &CsgOptions::exact())
+  }
+
+  /// Intersection with the given tolerances.
+  pub fn intersect_with(&self, csg: CSG, options: &CsgOptions) -> Self {
+    options.assert_valid();
+    self.clone().intersect_into(csg, options)
+  }
+
+  fn intersect_into(self, csg: CSG, options: &CsgOptions) -> Self {
+    // nothing outside the overlap of the bounding boxes is in both operands
+    let Operands {
+      mut a,
+      mut b,
+      merge_distance,
+      ..
+    } = Operands::new(self, csg, options);
     a.invert();
     b.clip_to(&mut a);
     b.invert();
@@ -177,9 +331,7 @@ impl CSG {
     b.clip_to(&mut a);
     a.build(b.all_polygons());
     a.invert();
-    CSG {
-      polygons: a.all_polygons(),
-    }
+    Self::from_result(a.all_polygons(), merge_distance)
   }
 }
 
@@ -187,11 +339,514 @@ impl std::ops::Mul<CSG> for CSG {
   type Output = Self;
 
   fn mul(self, rhs: CSG) -> Self::Output {
-    self.intersect(rhs)
+    self.intersect_into(rhs, &CsgOptions::default())
   }
 }
 
+impl CSG {
+  /// Union of any number of operands.
+  ///
+  /// The operands are combined in pairs, then the pairs in pairs and so on, so each
+  /// operand only goes through a few small booleans instead of one boolean with
+  /// everything before it. Operands that don't touch are put together without a
+  /// boolean.
+  ///
+  /// operands: The solids to join.
+  ///
+  /// return: The union of the operands.
+  pub fn union_all(operands: impl IntoIterator<Item = CSG>) -> CSG {
+    CSG::union_all_with(operands, &CsgOptions::default())
+  }
+
+  /// Union of any number of operands with the given tolerances. See [`CSG::union_all`].
+  pub fn union_all_with(operands: impl IntoIterator<Item = CSG>, options: &CsgOptions) -> CSG {
+    options.assert_valid();
+    union_balanced(operands.into_iter().collect(), options)
+  }
+
+  /// Subtract any number of tools from a base.
+  ///
+  /// Tools that don't touch the base are skipped and the rest are joined with
+  /// [`CSG::union_all`] and subtracted in one boolean.
+  ///
+  /// base: The solid to cut.
+  ///
+  /// tools: The solids to cut away.
+  ///
+  /// return: The base with the tools removed.
+  pub fn subtract_all(base: CSG, tools: impl IntoIterator<Item = CSG>) -> CSG {
+    CSG::subtract_all_with(base, tools, &CsgOptions::default())
+  }
+
+  /// Subtract any number of tools from a base with the given tolerances. See
+  /// [`CSG::subtract_all`].
+  pub fn subtract_all_with(
+    base: CSG,
+    tools: impl IntoIterator<Item = CSG>,
+    options: &CsgOptions,
+  ) -> CSG {
+    options.assert_valid();
+    let tools: Vec<CSG> = tools
+      .into_iter()
+      .filter(|tool| !disjoint(&base.polygons, &tool.polygons, options))
+      .collect();
+    if tools.is_empty() {
+      return base;
+    }
+    base.subtract_into(union_balanced(tools, options), options)
+  }
+}
+
+/// Join operands in a balanced tree of unions. With the `parallel` feature the two
+/// halves are joined at the same time.
+fn union_balanced(mut operands: Vec<CSG>, options: &CsgOptions) -> CSG {
+  if operands.len() <= 1 {
+    return operands.pop().unwrap_or_default();
+  }
+  let right = operands.split_off(operands.len() / 2);
+  let (mut a, mut b) = join(
+    || union_balanced(operands, options),
+    || union_balanced(right, options),
+  );
+  if disjoint(&a.polygons, &b.polygons, options) {
+    a.polygons.append(&mut b.polygons);
+    a
+  } else {
+    a.union_into(b, options)
+  }
+}
+
+/// Check if two solids are far enough apart that a boolean can't change either.
+fn disjoint(a: &[Polygon], b: &[Polygon], options: &CsgOptions) -> bool {
+  let (a_bounds, b_bounds) = match (bounds_of(a.iter()), bounds_of(b.iter())) {
+    (Some(a_bounds), Some(b_bounds)) => (a_bounds, b_bounds),
+    _ => return true,
+  };
+  // leave room for the tolerance and for snapping to move the operands together
+  let size = options.size(a.iter().chain(b.iter()));
+  let margin = options
+    .tolerance
+    .distance(size)
+    .max(options.merge_distance.distance(size))
+    + options.snap_grid.unwrap_or(0.0);
+  a_bounds.expanded(margin).intersection(&b_bounds).is_none()
+}
+
+/// The two operands of a boolean operation.
+///
+/// Only the parts of the operands inside the overlap of their bounding boxes can
+/// affect each other, so those go into the BSP trees and the rest is set aside.
+struct Operands {
+  a: Box<BSPNode>,
+  b: Box<BSPNode>,
+  /// The parts of a that are outside the bounding box of b.
+  a_outside: Vec<Polygon>,
+  /// The parts of b that are outside the bounding box of a.
+  b_outside: Vec<Polygon>,
+  /// The distance at which the result's vertices are merged.
+  merge_distance: f64,
+}
+
+impl Operands {
+  fn new(a: CSG, b: CSG, options: &CsgOptions) -> Self {
+    let mut a_polygons = a.polygons;
+    let mut b_polygons = b.polygons;
+    if let Some(grid) = options.snap_grid {
+      a_polygons = snap_to_grid(a_polygons, grid);
+      b_polygons = snap_to_grid(b_polygons, grid);
+    }
+    let size = options.size(a_polygons.iter().chain(b_polygons.iter()));
+    let classifier = Classifier {
+      precision: options.precision,
+      tolerance: options.tolerance.distance(size),
+    };
+
+    let mut a_outside = Vec::new();
+    let mut b_outside = Vec::new();
+    let overlap = bounds_of(a_polygons.iter())
+      .zip(bounds_of(b_polygons.iter()))
+      .map(|(a_bounds, b_bounds)| a_bounds.intersection(&b_bounds));
+    match overlap {
+      Some(None) => {
+        // disjoint, nothing to clip
+        a_outside = std::mem::take(&mut a_polygons);
+        b_outside = std::mem::take(&mut b_polygons);
+      }
+      Some(Some(overlap)) => {
+        // Keep the surfaces where they meet away from the cuts made by the box.
+        let margin = 10.0 * classifier.tolerance + 1.0e-3 * overlap.size().len();
+        let overlap = overlap.expanded(margin);
+        let (a_inside, a_rest) = split_by_box(&overlap, a_polygons.clone(), classifier);
+        let (b_inside, b_rest) = split_by_box(&overlap, b_polygons.clone(), classifier);
+        // A tree with no polygons calls everything outside, which is wrong when the
+        // box is inside the other operand, so only cull when both have polygons in it.
+        if a_inside.is_empty() == b_inside.is_empty() {
+          (a_polygons, a_outside) = (a_inside, a_rest);
+          (b_polygons, b_outside) = (b_inside, b_rest);
+        }
+      }
+      None => {}
+    }
+
+    let (a, b) = join(
+      || {
+        Box::new(BSPNode::with_settings(
+          a_polygons,
+          classifier,
+          options.split_strategy,
+        ))
+      },
+      || {
+        Box::new(BSPNode::with_settings(
+          b_polygons,
+          classifier,
+          options.split_strategy,
+        ))
+      },
+    );
+    Self {
+      a,
+      b,
+      a_outside,
+      b_outside,
+      merge_distance: options.merge_distance.distance(size),
+    }
+  }
+}
+
+/// Run two closures and return both results. With the `parallel` feature they run
+/// at the same time.
+fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+  A: FnOnce() -> RA + Send,
+  B: FnOnce() -> RB + Send,
+  RA: Send,
+  RB: Send,
+{
+  #[cfg(feature = "parallel")]
+  {
+    rayon::join(a, b)
+  }
+  #[cfg(not(feature = "parallel"))]
+  {
+    (a(), b())
+  }
+}
+
+/// The bounding box of some polygons.
+///
+/// return: The box or None if there are no vertices.
+fn bounds_of<'a>(polygons: impl Iterator<Item = &'a Polygon>) -> Option<Aabb> {
+  Aabb::from_points(polygons.flat_map(|p| p.vertices.iter().copied()))
+}
+
+/// Cut polygons into the parts inside and outside a box.
+///
+/// bounds: The box.
+///
+/// polygons: The polygons to cut.
+///
+/// classifier: Decides which side of the faces of the box a vertex is on.
+///
+/// return: The parts inside the box and the parts outside it.
+fn split_by_box(
+  bounds: &Aabb,
+  polygons: Vec<Polygon>,
+  classifier: Classifier,
+) -> (Vec<Polygon>, Vec<Polygon>) {
+  // the faces of the box with their normals pointing out
+  let faces = [
+    Plane::new(Pt3::new(-1.0, 0.0, 0.0), -bounds.min.x),
+    Plane::new(Pt3::new(1.0, 0.0, 0.0), bounds.max.x),
+    Plane::new(Pt3::new(0.0, -1.0, 0.0), -bounds.min.y),
+    Plane::new(Pt3::new(0.0, 1.0, 0.0), bounds.max.y),
+    Plane::new(Pt3::new(0.0, 0.0, -1.0), -bounds.min.z),
+    Plane::new(Pt3::new(0.0, 0.0, 1.0), bounds.max.z),
+  ];
+  let mut inside = Vec::new();
+  let mut outside = Vec::new();
+  for polygon in polygons {
+    let polygon_bounds = bounds_of(std::iter::once(&polygon)).unwrap();
+    match bounds.intersection(&polygon_bounds) {
+      None => outside.push(polygon),
+      Some(_) if bounds.contains_box(&polygon_bounds) => inside.push(polygon),
+      Some(_) => {
+        // whatever is in front of or on a face is outside
+        let mut pieces = vec![polygon];
+        for face in &faces {
+          let mut coplanar_front = Vec::new();
+          let mut coplanar_back = Vec::new();
+          let mut back = Vec::new();
+          for piece in pieces {
+            face.split_polygon(
+              piece,
+              classifier,
+              &mut coplanar_front,
+              &mut coplanar_back,
+              &mut outside,
+              &mut back,
+            );
+          }
+          outside.append(&mut coplanar_front);
+          outside.append(&mut coplanar_back);
+          pieces = back;
+        }
+        inside.append(&mut pieces);
+      }
+    }
+  }
+  (inside, outside)
+}
+
+/// Find a vertex of a convex polygon that can be used as the apex of a triangle fan
+/// without producing zero area triangles.
+fn fan_apex(vertices: &[Pt3], normal: Pt3) -> Option<usize> {
+  let n = vertices.len();
+  (0..n).find(|&apex| {
+    (1..(n - 1)).all(|i| {
+      let a = vertices[apex];
+      let b = vertices[(apex + i) % n];
+      let c = vertices[(apex + i + 1) % n];
+      // compare the sine of the angle at the apex so the test works at any scale
+      (b - a).cross(c - a).dot(normal) > Plane::EPSILON * (b - a).len() * (c - a).len()
+    })
+  })
+}
+
+/// How vertices are classified against the splitting planes of a BSP tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+  /// The distance of a vertex to a plane is computed in floating point.
+  #[default]
+  Epsilon,
+  /// Vertices are classified with the exact [`orient3d`] predicate against the three
+  /// vertices the plane was made from, so the sign of the distance to the polygon is
+  /// exact. A vertex within the tolerance of a plane still counts as on it, the
+  /// tolerance absorbs the rounding in the points created by splitting polygons and
+  /// scaling a model, which is why [`CsgOptions::exact`] makes it relative.
+  Exact,
+}
+
+/// A distance used by the boolean operations, in the units of the model or relative
+/// to the size of the operands.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+  /// A distance in the units of the model. The default suits models measured in
+  /// millimetres, very small models lose detail and very large models see cracks.
+  Absolute(f64),
+  /// A fraction of the diagonal of the bounding box around both operands, so the
+  /// result does not depend on the scale of the model.
+  Relative(f64),
+}
+
+impl Tolerance {
+  /// The distance for a boolean on operands of the given size.
+  ///
+  /// size: The diagonal of the bounding box around the operands.
+  fn distance(self, size: f64) -> f64 {
+    match self {
+      Tolerance::Absolute(distance) => distance,
+      Tolerance::Relative(fraction) => fraction * size,
+    }
+  }
+
+  fn value(self) -> f64 {
+    match self {
+      Tolerance::Absolute(value) | Tolerance::Relative(value) => value,
+    }
+  }
+}
+
+/// The diagonal of the bounding box around some polygons, used to turn relative
+/// tolerances into distances.
+///
+/// return: The diagonal or 1.0 if there are no polygons or they are unbounded.
+fn size_of<'a>(polygons: impl Iterator<Item = &'a Polygon>) -> f64 {
+  let size = bounds_of(polygons).map_or(0.0, |b| b.size().len());
+  if size.is_finite() && size > 0.0 {
+    size
+  } else {
+    1.0
+  }
+}
+
+/// Tolerances used by the boolean operations.
+///
+/// The defaults suit models measured in millimetres that are between a few tenths of
+/// a millimetre and a few metres in size. Very large models like buildings want a
+/// bigger tolerance and very small models like jewelry want a smaller one, or a
+/// [`Tolerance::Relative`] one.
+///
+/// The boolean operations panic if a tolerance, the merge distance or the snap grid
+/// is not positive and finite.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CsgOptions {
+  /// The distance from a plane at which a vertex is considered to be on the plane.
+  pub tolerance: Tolerance,
+  /// When set the vertices of both operands are rounded to multiples of this value
+  /// before the operation, which lines up features that should touch but were
+  /// modelled a hair apart.
+  pub snap_grid: Option<f64>,
+  /// Vertices of the result closer than this are merged into one.
+  pub merge_distance: Tolerance,
+  /// How vertices are classified.
+  pub precision: Precision,
+  /// How the BSP trees pick the plane that splits each node.
+  pub split_strategy: SplitStrategy,
+}
+
+impl Default for CsgOptions {
+  fn default() -> Self {
+    Self {
+      tolerance: Tolerance::Absolute(Plane::EPSILON),
+      snap_grid: None,
+      merge_distance: Tolerance::Absolute(Plane::EPSILON),
+      precision: Precision::Epsilon,
+      split_strategy: SplitStrategy::default(),
+    }
+  }
+}
+
+impl CsgOptions {
+  /// Options for [`Precision::Exact`] with tolerances of 1e-10 of the size of the
+  /// operands. They only absorb the rounding in the points created by splitting
+  /// polygons, and as fractions of the size a model classifies the same way at any
+  /// scale.
+  pub fn exact() -> Self {
+    Self {
+      tolerance: Tolerance::Relative(1.0e-10),
+      snap_grid: None,
+      merge_distance: Tolerance::Relative(1.0e-10),
+      precision: Precision::Exact,
+      split_strategy: SplitStrategy::default(),
+    }
+  }
+
+  /// Panic if a distance is not positive and finite. A zero merge distance divides by
+  /// zero when welding and a zero snap grid turns every vertex into NaN.
+  pub(crate) fn assert_valid(&self) {
+    let valid = |v: f64| v.is_finite() && v > 0.0;
+    assert!(
+      valid(self.tolerance.value()),
+      "the tolerance must be positive and finite"
+    );
+    assert!(
+      valid(self.merge_distance.value()),
+      "the merge distance must be positive and finite"
+    );
+    assert!(
+      self.snap_grid.map_or(true, valid),
+      "the snap grid must be positive and finite"
+    );
+  }
+
+  /// The size that relative tolerances are fractions of, only measured when one of
+  /// them is relative.
+  ///
+  /// polygons: The polygons of both operands.
+  fn size<'a>(&self, polygons: impl Iterator<Item = &'a Polygon>) -> f64 {
+    match (self.tolerance, self.merge_distance) {
+      (Tolerance::Absolute(_), Tolerance::Absolute(_)) => 1.0,
+      _ => size_of(polygons),
+    }
+  }
+}
+
+/// How a BSP tree picks the plane that splits the polygons of a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitStrategy {
+  /// The plane of the first polygon. This is cheap but gives deep, lopsided trees
+  /// and a lot of fragments on revolved and swept meshes.
+  First,
+  /// Score the planes of a sample of the polygons and use the best one.
+  Balanced {
+    /// The number of polygons, spread evenly through the list, whose planes are tried.
+    candidates: usize,
+    /// How many polygons of imbalance between the front and back a split is worth.
+    /// Higher values favour fewer fragments over shallower trees.
+    split_weight: f64,
+  },
+}
+
+impl Default for SplitStrategy {
+  fn default() -> Self {
+    SplitStrategy::Balanced {
+      candidates: 16,
+      split_weight: 4.0,
+    }
+  }
+}
+
+/// The most polygons each candidate plane of [`SplitStrategy::Balanced`] is scored
+/// against.
+const MAX_SCORED_POLYGONS: usize = 256;
+
+impl SplitStrategy {
+  /// Pick the splitting plane for a node.
+  ///
+  /// polygons: The polygons being added to the node, must not be empty.
+  ///
+  /// classifier: Decides which side of a plane a vertex is on, the same way the
+  /// polygons will be split.
+  ///
+  /// return: The plane to split the polygons with.
+  fn choose(self, polygons: &[Polygon], classifier: Classifier) -> Plane {
+    let (candidates, split_weight) = match self {
+      SplitStrategy::First => return polygons[0].plane,
+      SplitStrategy::Balanced {
+        candidates,
+        split_weight,
+      } => (candidates.clamp(1, polygons.len()), split_weight),
+    };
+    if candidates == 1 {
+      return polygons[0].plane;
+    }
+    // Scoring against every polygon would make building a convex mesh, where each node
+    // only takes one polygon off the list, quadratic in candidates times polygons.
+    let stride = (polygons.len() / MAX_SCORED_POLYGONS).max(1);
+    let mut best = polygons[0].plane;
+    let mut best_score = f64::MAX;
+    for i in 0..candidates {
+      let plane = polygons[i * polygons.len() / candidates].plane;
+      let distance = plane.distance(classifier.precision);
+      let mut n_front = 0usize;
+      let mut n_back = 0usize;
+      let mut n_split = 0usize;
+      for polygon in polygons.iter().step_by(stride) {
+        let mut in_front = false;
+        let mut behind = false;
+        for vertex in &polygon.vertices {
+          let t = distance(*vertex);
+          in_front |= t > classifier.tolerance;
+          behind |= t < -classifier.tolerance;
+        }
+        match (in_front, behind) {
+          (true, true) => n_split += 1,
+          (true, false) => n_front += 1,
+          (false, true) => n_back += 1,
+          (false, false) => {}
+        }
+      }
+      let score = split_weight * n_split as f64 + n_front.abs_diff(n_back) as f64;
+      if score < best_score {
+        best_score = score;
+        best = plane;
+      }
+    }
+    best
+  }
+}
+
+/// How a BSP tree decides which side of a plane a vertex is on.
+#[derive(Clone, Copy)]
+struct Classifier {
+  precision: Precision,
+  tolerance: f64,
+}
+
 pub struct BSPNode {
+  classifier: Classifier,
+  strategy: SplitStrategy,
   plane: Option<Plane>,
   front: Option<Box<BSPNode>>,
   back: Option<Box<BSPNode>>,
@@ -201,6 +856,11 @@ pub struct BSPNode {
 impl BSPNode {
   pub fn new(polygons: Option<Vec<Polygon>>) -> Self {
     let mut node = Self {
+      classifier: Classifier {
+        precision: Precision::Epsilon,
+        tolerance: Plane::EPSILON,
+      },
+      strategy: SplitStrategy::default(),
       plane: None,
       front: None,
       back: None,
@@ -212,98 +872,157 @@ impl BSPNode {
     node
   }
 
+  /// Create a tree that classifies vertices with the given options.
+  pub fn with_options(polygons: Vec<Polygon>, options: &CsgOptions) -> Self {
+    options.assert_valid();
+    let classifier = Classifier {
+      precision: options.precision,
+      tolerance: options.tolerance.distance(options.size(polygons.iter())),
+    };
+    Self::with_settings(polygons, classifier, options.split_strategy)
+  }
+
+  fn with_settings(
+    polygons: Vec<Polygon>,
+    classifier: Classifier,
+    strategy: SplitStrategy,
+  ) -> Self {
+    let mut node = Self::new(None);
+    node.classifier = classifier;
+    node.strategy = strategy;
+    node.build(polygons);
+    node
+  }
+
   pub fn invert(&mut self) {
-    for poly in &mut self.polygons {
-      poly.flip();
-    }
-    if self.plane.is_some() {
-      self.plane.as_mut().unwrap().flip();
-    }
-    if self.front.is_some() {
-      self.front.as_mut().unwrap().invert();
-    }
-    if self.back.is_some() {
-      self.back.as_mut().unwrap().invert();
+    let mut stack: Vec<&mut BSPNode> = vec![self];
+    while let Some(node) = stack.pop() {
+      for poly in &mut node.polygons {
+        poly.flip();
+      }
+      if let Some(plane) = node.plane.as_mut() {
+        plane.flip();
+      }
+      std::mem::swap(&mut node.front, &mut node.back);
+      if let Some(front) = node.front.as_deref_mut() {
+        stack.push(front);
+      }
+      if let Some(back) = node.back.as_deref_mut() {
+        stack.push(back);
+      }
     }
-    std::mem::swap(&mut self.front, &mut self.back);
   }
 
-  pub fn clip_polygons(&mut self, polygons: Vec<Polygon>) -> Vec<Polygon> {
-    if self.plane.is_none() {
-      return polygons;
-    }
-    let mut front: Vec<Polygon> = Vec::new();
-    let mut back: Vec<Polygon> = Vec::new();
-    for poly in polygons {
-      self
-        .plane
-        .unwrap()
-        .split_polygon(&poly, &mut front, &mut back, &mut front, &mut back)
-    }
-    if self.front.is_some() {
-      front = self.front.as_mut().unwrap().clip_polygons(front);
-    }
-    if self.back.is_some() {
-      back = self.back.as_mut().unwrap().clip_polygons(back);
-    } else {
-      back = Vec::new();
+  /// Remove the parts of polygons that are inside the solid represented by this tree.
+  pub fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+    let mut result: Vec<Polygon> = Vec::new();
+    // Back is pushed before front so the output order is the same as walking the
+    // front of each node before its back.
+    let mut stack: Vec<(&BSPNode, Vec<Polygon>)> = vec![(self, polygons)];
+    while let Some((node, polygons)) = stack.pop() {
+      let plane = match node.plane {
+        Some(plane) => plane,
+        None => {
+          result.extend(polygons);
+          continue;
+        }
+      };
+      let Split {
+        mut coplanar_front,
+        mut coplanar_back,
+        mut front,
+        mut back,
+      } = plane.split_polygons(polygons, node.classifier);
+      front.append(&mut coplanar_front);
+      back.append(&mut coplanar_back);
+      // polygons behind a leaf are inside the solid and are dropped
+      if let Some(back_node) = node.back.as_deref() {
+        stack.push((back_node, back));
+      }
+      match node.front.as_deref() {
+        Some(front_node) => stack.push((front_node, front)),
+        None => result.append(&mut front),
+      }
     }
-    front.append(&mut back);
-
-    front
+    result
   }
 
+  /// Remove the parts of this tree's polygons that are inside bsp.
   pub fn clip_to(&mut self, bsp: &mut Box<BSPNode>) {
-    self.polygons = bsp.clip_polygons(self.polygons.clone());
-    if self.front.is_some() {
-      self.front.as_mut().unwrap().clip_to(bsp)
-    }
-    if self.back.is_some() {
-      self.back.as_mut().unwrap().clip_to(bsp)
+    let mut stack: Vec<&mut BSPNode> = vec![self];
+    while let Some(node) = stack.pop() {
+      node.polygons = bsp.clip_polygons(std::mem::take(&mut node.polygons));
+      if let Some(back) = node.back.as_deref_mut() {
+        stack.push(back);
+      }
+      if let Some(front) = node.front.as_deref_mut() {
+        stack.push(front);
+      }
     }
   }
 
   pub fn all_polygons(&self) -> Vec<Polygon> {
-    let mut polygons = self.polygons.clone();
-    if self.front.is_some() {
-      polygons.append(&mut self.front.as_ref().unwrap().all_polygons());
-    }
-    if self.back.is_some() {
-      polygons.append(&mut self.back.as_ref().unwrap().all_polygons());
+    let mut polygons = Vec::new();
+    let mut stack: Vec<&BSPNode> = vec![self];
+    while let Some(node) = stack.pop() {
+      polygons.extend_from_slice(&node.polygons);
+      if let Some(back) = node.back.as_deref() {
+        stack.push(back);
+      }
+      if let Some(front) = node.front.as_deref() {
+        stack.push(front);
+      }
     }
     polygons
   }
 
   pub fn build(&mut self, polygons: Vec<Polygon>) {
-    if polygons.is_empty() {
-      return;
-    }
-    if self.plane.is_none() {
-      self.plane = Some(polygons[0].plane);
-    }
-    self.polygons.push(polygons[0].clone());
-    let mut front: Vec<Polygon> = Vec::new();
-    let mut back: Vec<Polygon> = Vec::new();
-    for polygon in polygons.iter().skip(1) {
-      self.plane.as_mut().unwrap().split_polygon(
-        polygon,
-        &mut self.polygons,
-        &mut self.polygons,
-        &mut front,
-        &mut back,
-      );
-    }
-    if !front.is_empty() {
-      if self.front.is_none() {
-        self.front = Some(Box::new(BSPNode::new(None)));
+    let mut stack: Vec<(&mut BSPNode, Vec<Polygon>)> = vec![(self, polygons)];
+    while let Some((node, polygons)) = stack.pop() {
+      if polygons.is_empty() {
+        continue;
       }
-      self.front.as_mut().unwrap().build(front);
-    }
-    if !back.is_empty() {
-      if self.back.is_none() {
-        self.back = Some(Box::new(BSPNode::new(None)));
+      let plane = match node.plane {
+        Some(plane) => plane,
+        None => *node
+          .plane
+          .insert(node.strategy.choose(&polygons, node.classifier)),
+      };
+      let Split {
+        mut coplanar_front,
+        mut coplanar_back,
+        front,
+        back,
+      } = plane.split_polygons(polygons, node.classifier);
+      node.polygons.append(&mut coplanar_front);
+      node.polygons.append(&mut coplanar_back);
+      let (classifier, strategy) = (node.classifier, node.strategy);
+      if !back.is_empty() {
+        let child = node.back.get_or_insert_with(|| {
+          Box::new(BSPNode::with_settings(Vec::new(), classifier, strategy))
+        });
+        stack.push((child, back));
+      }
+      if !front.is_empty() {
+        let child = node.front.get_or_insert_with(|| {
+          Box::new(BSPNode::with_settings(Vec::new(), classifier, strategy))
+        });
+        stack.push((child, front));
       }
-      self.back.as_mut().unwrap().build(back);
+    }
+  }
+}
+
+impl Drop for BSPNode {
+  // The default drop recurses once per level of the tree which can overflow the
+  // stack for deep trees, so unlink the children first.
+  fn drop(&mut self) {
+    let mut stack: Vec<Box<BSPNode>> = Vec::new();
+    stack.extend(self.front.take());
+    stack.extend(self.back.take());
+    while let Some(mut node) = stack.pop() {
+      stack.extend(node.front.take());
+      stack.extend(node.back.take());
     }
   }
 }
@@ -320,113 +1039,463 @@ impl Polygon {
     Self { vertices, plane }
   }
 
+  /// Creates a polygon that lies in a known plane. Used for the pieces of a split
+  /// polygon so they keep the exact plane of their parent instead of recomputing
+  /// one from what may be a thin sliver.
+  pub fn with_plane(vertices: Vec<Pt3>, plane: Plane) -> Self {
+    Self { vertices, plane }
+  }
+
   pub fn flip(&mut self) {
-    let n_verts = self.vertices.len();
-    let mut reversed = Vec::with_capacity(n_verts);
-    for i in 0..n_verts {
-      reversed.push(self.vertices[n_verts - 1 - i]);
-    }
-    self.vertices = reversed;
+    self.vertices.reverse();
     self.plane.flip();
   }
+
+  /// Transform by an affine matrix, flip says whether the matrix mirrors.
+  fn transform_winding(&mut self, matrix: &Mt4, flip: bool) {
+    for v in &mut self.vertices {
+      v.transform(matrix);
+    }
+    let [a, b, c] = self.plane.points().map(|p| p.transformed(matrix));
+    if flip {
+      self.vertices.reverse();
+      self.plane = Plane::from_points(a, c, b);
+    } else {
+      self.plane = Plane::from_points(a, b, c);
+    }
+  }
+}
+
+/// Lists shorter than this are split on one thread even with the `parallel` feature.
+#[cfg(feature = "parallel")]
+const PARALLEL_SPLIT_LEN: usize = 1024;
+
+/// The polygons on each side of a plane.
+#[derive(Default)]
+struct Split {
+  coplanar_front: Vec<Polygon>,
+  coplanar_back: Vec<Polygon>,
+  front: Vec<Polygon>,
+  back: Vec<Polygon>,
+}
+
+impl Split {
+  fn push(&mut self, plane: &Plane, polygon: Polygon, classifier: Classifier) {
+    plane.split_polygon(
+      polygon,
+      classifier,
+      &mut self.coplanar_front,
+      &mut self.coplanar_back,
+      &mut self.front,
+      &mut self.back,
+    );
+  }
+
+  #[cfg(feature = "parallel")]
+  fn append(&mut self, other: &mut Split) {
+    self.coplanar_front.append(&mut other.coplanar_front);
+    self.coplanar_back.append(&mut other.coplanar_back);
+    self.front.append(&mut other.front);
+    self.back.append(&mut other.back);
+  }
 }
 
 #[derive(Clone, Copy)]
 pub struct Plane {
   pub normal: Pt3,
   pub w: f64,
+  /// The points the plane was made from, None for a plane made from a normal and w.
+  pub(crate) points: Option<[Pt3; 3]>,
 }
 
 impl Plane {
+  /// The distance from a plane that a point is considered to be on it.
+  pub const EPSILON: f64 = 1.0e-5;
+
   pub fn new(normal: Pt3, w: f64) -> Self {
-    Self { normal, w }
+    Self {
+      normal,
+      w,
+      points: None,
+    }
   }
 
+  /// The plane through three points, ccw around the normal. The exact orientation test
+  /// uses the points themselves, so a vertex is classified against the polygon the
+  /// plane came from and not against the rounded normal and w.
   pub fn from_points(a: Pt3, b: Pt3, c: Pt3) -> Self {
     let n = (b - a).cross(c - a).normalized();
-    Self::new(n, n.dot(a))
+    Self {
+      normal: n,
+      w: n.dot(a),
+      points: Some([a, b, c]),
+    }
   }
 
   pub fn flip(&mut self) {
     self.normal = -self.normal;
     self.w = -self.w;
+    if let Some([a, b, c]) = self.points {
+      self.points = Some([a, c, b]);
+    }
+  }
+
+  /// Three points on the plane in ccw order around the normal for the exact
+  /// orientation test. These are the points the plane was made from, or points built
+  /// from the normal and w when there are none.
+  fn points(&self) -> [Pt3; 3] {
+    if let Some(points) = self.points {
+      return points;
+    }
+    // pick two directions in the plane that don't change sign when the plane flips
+    let (normal, w, flipped) = if lexicographic_less(self.normal, -self.normal) {
+      (-self.normal, -self.w, true)
+    } else {
+      (self.normal, self.w, false)
+    };
+    let helper = if normal.x.abs() < 0.9 {
+      Pt3::new(1.0, 0.0, 0.0)
+    } else {
+      Pt3::new(0.0, 1.0, 0.0)
+    };
+    let u = normal.cross(helper).normalized();
+    let v = normal.cross(u);
+    let origin = normal * w;
+    if flipped {
+      [origin, origin + v, origin + u]
+    } else {
+      [origin, origin + u, origin + v]
+    }
+  }
+
+  /// How far vertices are from the plane, positive in front of it.
+  ///
+  /// precision: How the distance is computed.
+  ///
+  /// return: The distance of a vertex.
+  fn distance(&self, precision: Precision) -> impl Fn(Pt3) -> f64 {
+    let Plane { normal, w, .. } = *self;
+    let exact = match precision {
+      Precision::Epsilon => None,
+      Precision::Exact => {
+        let [a, b, c] = self.points();
+        Some(([a, b, c], (b - a).cross(c - a).len()))
+      }
+    };
+    move |vertex| match exact {
+      None => normal.dot(vertex) - w,
+      // orient3d is six times the volume of the tetrahedron, dividing by twice the
+      // area of the base gives the distance with an exact sign
+      Some(([a, b, c], area)) => -orient3d(a, b, c, vertex) / area,
+    }
+  }
+
+  /// Whether both planes are the same plane, in either orientation.
+  fn is_same_plane(&self, other: &Plane) -> bool {
+    (self.normal == other.normal && self.w == other.w)
+      || (self.normal == -other.normal && self.w == -other.w)
+  }
+
+  /// The point where the segment a b crosses the plane.
+  ///
+  /// The result only depends on the unordered pair {a, b}, so two polygons that
+  /// share an edge get the exact same new vertex when the edge is split.
+  fn intersect_edge(&self, a: Pt3, b: Pt3) -> Pt3 {
+    let (p, q) = if lexicographic_less(a, b) {
+      (a, b)
+    } else {
+      (b, a)
+    };
+    let t = (self.w - self.normal.dot(p)) / self.normal.dot(q - p);
+    p.lerp(q, t.clamp(0.0, 1.0))
+  }
+
+  /// Split a list of polygons by this plane, keeping them in the same order. With the
+  /// `parallel` feature long lists are split across threads.
+  fn split_polygons(&self, polygons: Vec<Polygon>, classifier: Classifier) -> Split {
+    #[cfg(feature = "parallel")]
+    if polygons.len() >= PARALLEL_SPLIT_LEN {
+      use rayon::prelude::*;
+      // rayon reduces neighbouring pieces in order so the result matches the serial path
+      return polygons
+        .into_par_iter()
+        .fold(Split::default, |mut split, polygon| {
+          split.push(self, polygon, classifier);
+          split
+        })
+        .reduce(Split::default, |mut a, mut b| {
+          a.append(&mut b);
+          a
+        });
+    }
+    let mut split = Split::default();
+    for polygon in polygons {
+      split.push(self, polygon, classifier);
+    }
+    split
   }
 
   fn split_polygon(
     &self,
-    polygon: &Polygon,
-    coplanar_front: *mut Vec<Polygon>,
-    coplanar_back: *mut Vec<Polygon>,
-    front: *mut Vec<Polygon>,
-    back: *mut Vec<Polygon>,
+    polygon: Polygon,
+    classifier: Classifier,
+    coplanar_front: &mut Vec<Polygon>,
+    coplanar_back: &mut Vec<Polygon>,
+    front: &mut Vec<Polygon>,
+    back: &mut Vec<Polygon>,
   ) {
-    const EPSILON: f64 = 1.0e-5;
     const COPLANAR: u32 = 0;
     const FRONT: u32 = 1;
     const BACK: u32 = 2;
     const SPANNING: u32 = 3;
 
+    // A polygon is always on its own plane even when rounding in the points created by
+    // earlier splits puts them a hair off it.
+    if self.is_same_plane(&polygon.plane) {
+      if self.normal.dot(polygon.plane.normal) > 0.0 {
+        coplanar_front.push(polygon);
+      } else {
+        coplanar_back.push(polygon);
+      }
+      return;
+    }
+
+    let distance = self.distance(classifier.precision);
     let mut polygon_type = 0;
     let n_vertices = polygon.vertices.len();
     let mut vertex_locs = Vec::with_capacity(n_vertices);
-    for i in 0..n_vertices {
-      let t = self.normal.dot(polygon.vertices[i]) - self.w;
+    for vertex in &polygon.vertices {
+      let t = distance(*vertex);
       let mut loc = COPLANAR;
-      if t < -EPSILON {
+      if t < -classifier.tolerance {
         loc = BACK;
-      } else if t > EPSILON {
+      } else if t > classifier.tolerance {
         loc = FRONT;
       }
       polygon_type |= loc;
       vertex_locs.push(loc);
     }
 
-    if polygon_type == COPLANAR {
-      if self.normal.dot(polygon.plane.normal) > 0.0 {
-        unsafe {
-          (*coplanar_front).push(polygon.clone());
-        }
-      } else {
-        unsafe {
-          (*coplanar_back).push(polygon.clone());
+    match polygon_type {
+      COPLANAR => {
+        if self.normal.dot(polygon.plane.normal) > 0.0 {
+          coplanar_front.push(polygon);
+        } else {
+          coplanar_back.push(polygon);
         }
       }
-    } else if polygon_type == FRONT {
-      unsafe {
-        (*front).push(polygon.clone());
-      }
-    } else if polygon_type == BACK {
-      unsafe {
-        (*back).push(polygon.clone());
-      }
-    } else if polygon_type == SPANNING {
-      let mut f = Vec::new();
-      let mut b = Vec::new();
-      for i in 0..n_vertices {
-        let j = (i + 1) % n_vertices;
-        let ti = vertex_locs[i];
-        let tj = vertex_locs[j];
-        let vi = polygon.vertices[i];
-        let vj = polygon.vertices[j];
-        if ti != BACK {
-          f.push(vi);
+      FRONT => front.push(polygon),
+      BACK => back.push(polygon),
+      _ => {
+        let mut f = Vec::with_capacity(n_vertices + 1);
+        let mut b = Vec::with_capacity(n_vertices + 1);
+        for i in 0..n_vertices {
+          let j = (i + 1) % n_vertices;
+          let ti = vertex_locs[i];
+          let tj = vertex_locs[j];
+          let vi = polygon.vertices[i];
+          let vj = polygon.vertices[j];
+          if ti != BACK {
+            push_distinct(&mut f, vi);
+          }
+          if ti != FRONT {
+            push_distinct(&mut b, vi);
+          }
+          if (ti | tj) == SPANNING {
+            let v = self.intersect_edge(vi, vj);
+            push_distinct(&mut f, v);
+            push_distinct(&mut b, v);
+          }
         }
-        if ti != FRONT {
-          b.push(vi);
+        if close_loop(&mut f) >= 3 {
+          front.push(Polygon::with_plane(f, polygon.plane));
         }
-        if (ti | tj) == SPANNING {
-          let t = (self.w - self.normal.dot(vi)) / self.normal.dot(vj - vi);
-          let v = vi.lerp(vj, t);
-          f.push(v);
-          b.push(v);
+        if close_loop(&mut b) >= 3 {
+          back.push(Polygon::with_plane(b, polygon.plane));
         }
       }
-      if f.len() >= 3 {
-        unsafe { (*front).push(Polygon::new(f)) };
+    }
+  }
+}
+
+/// Orders points by x, then y, then z.
+fn lexicographic_less(a: Pt3, b: Pt3) -> bool {
+  (a.x, a.y, a.z) < (b.x, b.y, b.z)
+}
+
+/// Push a vertex onto a polygon under construction unless it repeats the last one.
+fn push_distinct(vertices: &mut Vec<Pt3>, v: Pt3) {
+  if vertices.last() != Some(&v) {
+    vertices.push(v);
+  }
+}
+
+/// Drop a closing vertex that repeats the first one and return the vertex count.
+fn close_loop(vertices: &mut Vec<Pt3>) -> usize {
+  while vertices.len() > 1 && vertices.first() == vertices.last() {
+    vertices.pop();
+  }
+  vertices.len()
+}
+
+/// Round the vertices of polygons to multiples of grid. Polygons that collapse are
+/// removed and the rest get their planes recomputed.
+fn snap_to_grid(polygons: Vec<Polygon>, grid: f64) -> Vec<Polygon> {
+  let round = |v: f64| (v / grid).round() * grid;
+  let mut snapped = Vec::with_capacity(polygons.len());
+  for polygon in polygons {
+    let mut vertices = Vec::with_capacity(polygon.vertices.len());
+    for v in polygon.vertices {
+      push_distinct(&mut vertices, Pt3::new(round(v.x), round(v.y), round(v.z)));
+    }
+    if close_loop(&mut vertices) < 3 {
+      continue;
+    }
+    let plane = Plane::from_points(vertices[0], vertices[1], vertices[2]);
+    if plane.normal.x.is_finite() && plane.normal.y.is_finite() && plane.normal.z.is_finite() {
+      snapped.push(Polygon::with_plane(vertices, plane));
+    }
+  }
+  snapped
+}
+
+/// Snaps vertices that are within tolerance of each other to a single position.
+///
+/// Different planes cutting through the same spot compute intersection points that
+/// differ in the last few bits. Left alone those near duplicates show up as cracks and
+/// zero area slivers. Polygons that collapse to fewer than three vertices are removed.
+fn weld_vertices(polygons: &mut Vec<Polygon>, tolerance: f64) {
+  let mut welder = Welder::new(tolerance);
+  for polygon in polygons.iter_mut() {
+    let mut vertices = Vec::with_capacity(polygon.vertices.len());
+    for v in &polygon.vertices {
+      push_distinct(&mut vertices, welder.weld(*v));
+    }
+    close_loop(&mut vertices);
+    polygon.vertices = vertices;
+  }
+  polygons.retain(|polygon| polygon.vertices.len() >= 3);
+}
+
+/// Removes polygons that are thinner than tolerance.
+///
+/// A sliver covers a vertex lying on the edge of its neighbour, so removing it leaves a
+/// T-junction that [`fix_t_junctions`] closes again.
+fn remove_slivers(polygons: &mut Vec<Polygon>, tolerance: f64) {
+  polygons.retain(|polygon| {
+    let vertices = &polygon.vertices;
+    let n = vertices.len();
+    let mut area = Pt3::new(0.0, 0.0, 0.0);
+    let mut longest: f64 = 0.0;
+    for i in 0..n {
+      let a = vertices[i];
+      let b = vertices[(i + 1) % n];
+      area += (a - vertices[0]).cross(b - vertices[0]);
+      longest = longest.max((b - a).len());
+    }
+    // twice the area over the longest edge is the width of a triangle
+    area.len() > tolerance * longest
+  });
+}
+
+/// How many times [`fix_t_junctions`] goes over the polygons at most.
+const T_JUNCTION_PASSES: usize = 4;
+
+/// Splits polygon edges at vertices of neighbouring polygons that lie on them.
+///
+/// The BSP clipping cuts a face on one side of an edge without cutting the face on
+/// the other side, which leaves a T-junction: a vertex sitting in the middle of the
+/// neighbour's edge. Inserting that vertex into the neighbour's edge makes every edge
+/// in the result shared by exactly two polygons again.
+///
+/// Inserting points can only close edges, but the newly created edges may need points
+/// of their own, so this repeats until nothing changes. Boolean results settle after
+/// one or two passes, edges that are still open after [`T_JUNCTION_PASSES`] fail a
+/// debug assertion and are left open in release builds.
+fn fix_t_junctions(polygons: &mut [Polygon], tolerance: f64) {
+  use std::collections::{HashMap, HashSet};
+
+  for pass in 1..=T_JUNCTION_PASSES {
+    let mut edge_counts: HashMap<([u64; 3], [u64; 3]), i32> = HashMap::new();
+    for polygon in polygons.iter() {
+      let n = polygon.vertices.len();
+      for i in 0..n {
+        let a = vertex_key(polygon.vertices[i]);
+        let b = vertex_key(polygon.vertices[(i + 1) % n]);
+        *edge_counts.entry((a, b)).or_insert(0) += 1;
       }
-      if b.len() >= 3 {
-        unsafe { (*back).push(Polygon::new(b)) };
+    }
+    let is_open = |a: [u64; 3], b: [u64; 3]| {
+      edge_counts.get(&(b, a)).copied().unwrap_or(0) < edge_counts[&(a, b)]
+    };
+
+    let mut seen = HashSet::new();
+    let mut candidates: Vec<Pt3> = Vec::new();
+    for polygon in polygons.iter() {
+      let n = polygon.vertices.len();
+      for i in 0..n {
+        let a = polygon.vertices[i];
+        let b = polygon.vertices[(i + 1) % n];
+        if is_open(vertex_key(a), vertex_key(b)) {
+          for v in [a, b] {
+            if seen.insert(vertex_key(v)) {
+              candidates.push(v);
+            }
+          }
+        }
       }
     }
+    if candidates.is_empty() {
+      return;
+    }
+    candidates.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+    let mut changed = false;
+    for polygon in polygons.iter_mut() {
+      let n = polygon.vertices.len();
+      let mut vertices = Vec::with_capacity(n);
+      for i in 0..n {
+        let a = polygon.vertices[i];
+        let b = polygon.vertices[(i + 1) % n];
+        vertices.push(a);
+        if !is_open(vertex_key(a), vertex_key(b)) {
+          continue;
+        }
+        let ab = b - a;
+        let len2 = ab.len2();
+        if len2 == 0.0 {
+          continue;
+        }
+        let min_x = a.x.min(b.x) - tolerance;
+        let max_x = a.x.max(b.x) + tolerance;
+        let start = candidates.partition_point(|v| v.x < min_x);
+        let mut on_edge: Vec<(f64, Pt3)> = Vec::new();
+        for v in candidates[start..].iter().take_while(|v| v.x <= max_x) {
+          if *v == a || *v == b {
+            continue;
+          }
+          let t = (*v - a).dot(ab) / len2;
+          if t <= 0.0 || t >= 1.0 {
+            continue;
+          }
+          if (a.lerp(b, t) - *v).len2() <= tolerance * tolerance {
+            on_edge.push((t, *v));
+          }
+        }
+        if !on_edge.is_empty() {
+          on_edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+          vertices.extend(on_edge.into_iter().map(|(_, v)| v));
+          changed = true;
+        }
+      }
+      polygon.vertices = vertices;
+    }
+    if !changed {
+      return;
+    }
+    debug_assert!(
+      pass < T_JUNCTION_PASSES,
+      "T-junctions are still open after {T_JUNCTION_PASSES} passes"
+    );
   }
 }
@@ -80,6 +80,21 @@ fn in_triangle(p: &(usize, Pt2), a: &(usize, Pt2), b: &(usize, Pt2), c: &(usize,
 /// return: An array of indices into the given vertex array.
 pub fn triangulate3d(vertices: &[Pt3], normal: Pt3) -> Vec<usize> {
   assert!(vertices.len() > 3);
+  try_triangulate3d(vertices, normal).expect("the polygon can't be triangulated")
+}
+
+/// Triangulate a 3D polygon like [`triangulate3d`] without panicking.
+///
+/// vertices: The vertices of the polygon, at least three.
+///
+/// normal: The normal of the polygon.
+///
+/// return: An array of indices into the given vertex array or None if the polygon
+/// isn't counter clockwise around the normal or no ear could be found.
+pub(crate) fn try_triangulate3d(vertices: &[Pt3], normal: Pt3) -> Option<Vec<usize>> {
+  if vertices.len() < 3 {
+    return None;
+  }
   const PX: u8 = 1;
   const NX: u8 = 2;
   const PY: u8 = 3;
@@ -174,7 +189,9 @@ pub fn triangulate3d(vertices: &[Pt3], normal: Pt3) -> Vec<usize> {
       index + 1
     }],
   ];
-  assert!(is_ccw(&tri));
+  if !is_ccw(&tri) {
+    return None;
+  }
 
   while polygon.len() >= 3 {
     let mut eartip = -1i16;
@@ -241,8 +258,10 @@ pub fn triangulate3d(vertices: &[Pt3], normal: Pt3) -> Vec<usize> {
     polygon.remove(eartip as usize);
   } // while polygon.len()
 
-  assert!(triangles.len() == (vertices.len() - 2) * 3);
-  triangles
+  if triangles.len() != (vertices.len() - 2) * 3 {
+    return None;
+  }
+  Some(triangles)
 }
 
 /// Triangulate a 2D polygon
@@ -354,3 +373,39 @@ pub fn triangulate2d(vertices: &[Pt2]) -> Vec<usize> {
 
   triangles
 }
+
+/// Project a point onto the coordinate plane most parallel to a plane, keeping
+/// counter clockwise around the normal counter clockwise in 2D.
+///
+/// p: The point.
+///
+/// normal: The normal of the plane.
+///
+/// return: The projected point.
+pub(crate) fn project(p: Pt3, normal: Pt3) -> Pt2 {
+  let (ax, ay, az) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+  if az >= ax && az >= ay {
+    if normal.z >= 0.0 {
+      Pt2::new(p.x, p.y)
+    } else {
+      Pt2::new(p.y, p.x)
+    }
+  } else if ax >= ay {
+    if normal.x >= 0.0 {
+      Pt2::new(p.y, p.z)
+    } else {
+      Pt2::new(p.z, p.y)
+    }
+  } else if normal.y >= 0.0 {
+    Pt2::new(p.z, p.x)
+  } else {
+    Pt2::new(p.x, p.z)
+  }
+}
+
+/// Twice the signed area of the triangle o, a, b.
+///
+/// return: The area, positive when counter clockwise.
+pub(crate) fn cross(o: Pt2, a: Pt2, b: Pt2) -> f64 {
+  (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
@@ -0,0 +1,77 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The error type of the fallible operations, mostly reading and writing files.
+
+/// Why reading or writing a file failed.
+#[derive(Debug)]
+pub enum Error {
+  /// The reader or writer failed, including files that can't be opened.
+  Io(std::io::Error),
+  /// The data ended early, the sizes are in bytes.
+  Truncated { expected: usize, found: usize },
+  /// Text that doesn't follow the format, with the line it was found on counting from 1.
+  Syntax { line: usize, message: String },
+  /// A binary file holds a different number of triangles than its header says.
+  TriangleCount { expected: usize, found: usize },
+}
+
+/// A result with the error type of this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Io(error) => write!(f, "{}", error),
+      Self::Truncated { expected, found } => {
+        write!(
+          f,
+          "expected {} bytes but the data ends after {}",
+          expected, found
+        )
+      }
+      Self::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+      Self::TriangleCount { expected, found } => {
+        write!(
+          f,
+          "the header says {} triangles but there are {}",
+          expected, found
+        )
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Io(error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+impl From<std::io::Error> for Error {
+  fn from(error: std::io::Error) -> Self {
+    Self::Io(error)
+  }
+}
@@ -0,0 +1,333 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A half-edge structure for questions about the connectivity of a mesh.
+//!
+//! Every triangle has three half-edges that go around it counter clockwise. The
+//! half-edge on the other side of the same edge is its twin, edges on the boundary
+//! of an open mesh have no twin. From there the triangles around an edge, the ring
+//! of vertices around a vertex and the boundary loops are a few steps away. Only
+//! manifold meshes can be stored like this, anything else is reported as an error.
+
+use {
+  crate::{IndexedMesh, Mesh, Pt3},
+  std::collections::HashMap,
+};
+
+/// Why a mesh can't be made into a half-edge mesh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopologyError {
+  /// The triangle uses the same vertex more than once.
+  DegenerateFace(usize),
+  /// The edge from the first vertex to the second is used by more than one triangle,
+  /// so more than two triangles meet at the edge or two of them face opposite ways.
+  NonManifoldEdge(usize, usize),
+  /// The triangles around the vertex don't form a single fan, like two cones that
+  /// touch at their tips.
+  NonManifoldVertex(usize),
+}
+
+impl std::fmt::Display for TopologyError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::DegenerateFace(face) => write!(f, "triangle {} uses a vertex twice", face),
+      Self::NonManifoldEdge(a, b) => {
+        write!(
+          f,
+          "edge from vertex {} to {} is used by more than one triangle",
+          a, b
+        )
+      }
+      Self::NonManifoldVertex(v) => write!(f, "triangles around vertex {} are not one fan", v),
+    }
+  }
+}
+
+impl std::error::Error for TopologyError {}
+
+/// One side of an edge, going counter clockwise around its triangle.
+#[derive(Clone, Copy, Debug)]
+pub struct HalfEdge {
+  /// The vertex the half-edge starts at.
+  pub origin: usize,
+  /// The half-edge going the other way on the same edge, None on the boundary.
+  pub twin: Option<usize>,
+  /// The next half-edge around the triangle.
+  pub next: usize,
+  /// The triangle the half-edge belongs to.
+  pub face: usize,
+}
+
+/// A manifold triangle mesh with adjacency. The half-edges of triangle f are 3 * f,
+/// 3 * f + 1 and 3 * f + 2.
+#[derive(Clone)]
+pub struct HalfEdgeMesh {
+  vertices: Vec<Pt3>,
+  half_edges: Vec<HalfEdge>,
+  /// A half-edge out of each vertex, the one on the boundary for boundary vertices
+  /// so circulating from it sees every triangle. None for unused vertices.
+  outgoing: Vec<Option<usize>>,
+}
+
+impl HalfEdgeMesh {
+  /// Creates a half-edge mesh from an indexed mesh.
+  ///
+  /// mesh: The indexed mesh.
+  ///
+  /// return: The half-edge mesh or the first problem that makes the mesh non-manifold.
+  pub fn from_indexed(mesh: &IndexedMesh) -> Result<Self, TopologyError> {
+    let mut half_edges = Vec::with_capacity(mesh.triangles.len() * 3);
+    let mut directed: HashMap<(usize, usize), usize> =
+      HashMap::with_capacity(half_edges.capacity());
+    for (face, triangle) in mesh.triangles.iter().enumerate() {
+      let [a, b, c] = *triangle;
+      if a == b || b == c || c == a {
+        return Err(TopologyError::DegenerateFace(face));
+      }
+      for k in 0..3 {
+        let origin = triangle[k];
+        let target = triangle[(k + 1) % 3];
+        if directed
+          .insert((origin, target), half_edges.len())
+          .is_some()
+        {
+          return Err(TopologyError::NonManifoldEdge(origin, target));
+        }
+        half_edges.push(HalfEdge {
+          origin,
+          twin: None,
+          next: face * 3 + (k + 1) % 3,
+          face,
+        });
+      }
+    }
+
+    let mut outgoing = vec![None; mesh.vertices.len()];
+    let mut face_counts = vec![0usize; mesh.vertices.len()];
+    for h in 0..half_edges.len() {
+      let origin = half_edges[h].origin;
+      let target = half_edges[half_edges[h].next].origin;
+      let twin = directed.get(&(target, origin)).copied();
+      half_edges[h].twin = twin;
+      if twin.is_none() || outgoing[origin].is_none() {
+        outgoing[origin] = Some(h);
+      }
+      face_counts[origin] += 1;
+    }
+
+    let mesh = Self {
+      vertices: mesh.vertices.clone(),
+      half_edges,
+      outgoing,
+    };
+    // a vertex with more than one fan only sees one of them when circulating
+    for (v, &count) in face_counts.iter().enumerate() {
+      if mesh.outgoing_half_edges(v).count() != count {
+        return Err(TopologyError::NonManifoldVertex(v));
+      }
+    }
+    Ok(mesh)
+  }
+
+  /// Creates a half-edge mesh from a mesh, points that are exactly equal are the same
+  /// vertex.
+  ///
+  /// mesh: The mesh.
+  ///
+  /// return: The half-edge mesh or the first problem that makes the mesh non-manifold.
+  pub fn from_mesh(mesh: &Mesh) -> Result<Self, TopologyError> {
+    Self::from_indexed(&IndexedMesh::from_mesh(mesh))
+  }
+
+  /// Convert back to an indexed mesh with the same vertices and triangles.
+  ///
+  /// return: The indexed mesh.
+  pub fn to_indexed(&self) -> IndexedMesh {
+    IndexedMesh {
+      vertices: self.vertices.clone(),
+      triangles: (0..self.face_count())
+        .map(|f| self.face_vertices(f))
+        .collect(),
+    }
+  }
+
+  /// The positions of the vertices.
+  pub fn vertices(&self) -> &[Pt3] {
+    &self.vertices
+  }
+
+  /// All the half-edges.
+  pub fn half_edges(&self) -> &[HalfEdge] {
+    &self.half_edges
+  }
+
+  /// The number of triangles.
+  pub fn face_count(&self) -> usize {
+    self.half_edges.len() / 3
+  }
+
+  /// The vertex a half-edge ends at.
+  ///
+  /// h: The half-edge.
+  ///
+  /// return: The vertex index.
+  pub fn target(&self, h: usize) -> usize {
+    self.half_edges[self.half_edges[h].next].origin
+  }
+
+  /// The half-edge before h around its triangle.
+  ///
+  /// h: The half-edge.
+  ///
+  /// return: The previous half-edge.
+  pub fn prev(&self, h: usize) -> usize {
+    self.half_edges[self.half_edges[h].next].next
+  }
+
+  /// The vertices of a triangle in counter clockwise order.
+  ///
+  /// face: The triangle.
+  ///
+  /// return: The vertex indices.
+  pub fn face_vertices(&self, face: usize) -> [usize; 3] {
+    [
+      self.half_edges[face * 3].origin,
+      self.half_edges[face * 3 + 1].origin,
+      self.half_edges[face * 3 + 2].origin,
+    ]
+  }
+
+  /// The triangles that share an edge with a triangle, None across boundary edges.
+  ///
+  /// face: The triangle.
+  ///
+  /// return: The neighbours across the edges starting at each vertex of the triangle.
+  pub fn face_neighbours(&self, face: usize) -> [Option<usize>; 3] {
+    [0, 1, 2].map(|k| {
+      self.half_edges[face * 3 + k]
+        .twin
+        .map(|t| self.half_edges[t].face)
+    })
+  }
+
+  /// Find the half-edge going from vertex a to vertex b.
+  ///
+  /// a: The start vertex.
+  ///
+  /// b: The end vertex.
+  ///
+  /// return: The half-edge or None if there is no triangle with that edge in that
+  /// direction.
+  pub fn find_half_edge(&self, a: usize, b: usize) -> Option<usize> {
+    self.outgoing_half_edges(a).find(|&h| self.target(h) == b)
+  }
+
+  /// The triangles on either side of the edge between vertices a and b.
+  ///
+  /// a: One end of the edge.
+  ///
+  /// b: The other end of the edge.
+  ///
+  /// return: The triangle that goes from a to b and the one that goes from b to a.
+  pub fn edge_faces(&self, a: usize, b: usize) -> (Option<usize>, Option<usize>) {
+    let face = |a, b| self.find_half_edge(a, b).map(|h| self.half_edges[h].face);
+    (face(a, b), face(b, a))
+  }
+
+  /// Whether a vertex is on the boundary of the mesh.
+  pub fn is_boundary_vertex(&self, v: usize) -> bool {
+    self.outgoing[v].is_some_and(|h| self.half_edges[h].twin.is_none())
+  }
+
+  /// Circulate the half-edges that start at a vertex, counter clockwise.
+  ///
+  /// v: The vertex.
+  ///
+  /// return: An iterator over the half-edges.
+  pub fn outgoing_half_edges(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+    let start = self.outgoing[v];
+    let mut current = start;
+    std::iter::from_fn(move || {
+      let h = current?;
+      // the half-edge into v from the same triangle, its twin is the next one out
+      current = self.half_edges[self.prev(h)]
+        .twin
+        .filter(|&n| Some(n) != start);
+      Some(h)
+    })
+  }
+
+  /// Circulate the triangles around a vertex, counter clockwise.
+  ///
+  /// v: The vertex.
+  ///
+  /// return: An iterator over the triangles.
+  pub fn vertex_faces(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+    self.outgoing_half_edges(v).map(|h| self.half_edges[h].face)
+  }
+
+  /// The vertices connected to a vertex by an edge, counter clockwise.
+  ///
+  /// v: The vertex.
+  ///
+  /// return: The one-ring of the vertex.
+  pub fn one_ring(&self, v: usize) -> Vec<usize> {
+    let mut ring = Vec::new();
+    let mut last = None;
+    for h in self.outgoing_half_edges(v) {
+      ring.push(self.target(h));
+      last = Some(h);
+    }
+    // on the boundary the last neighbour has no half-edge out of v going to it
+    if let Some(h) = last {
+      let prev = self.prev(h);
+      if self.half_edges[prev].twin.is_none() {
+        ring.push(self.half_edges[prev].origin);
+      }
+    }
+    ring
+  }
+
+  /// Find the loops of edges that only have a triangle on one side.
+  ///
+  /// return: The vertices of each loop in the direction of the triangles next to it,
+  /// a patch that closes a loop goes around it the other way.
+  pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; self.half_edges.len()];
+    let mut loops = Vec::new();
+    for start in 0..self.half_edges.len() {
+      if visited[start] || self.half_edges[start].twin.is_some() {
+        continue;
+      }
+      let mut boundary = Vec::new();
+      let mut h = start;
+      while !visited[h] {
+        visited[h] = true;
+        boundary.push(self.half_edges[h].origin);
+        // a manifold boundary vertex has one boundary half-edge going out of it
+        h = self.outgoing[self.target(h)].unwrap();
+      }
+      loops.push(boundary);
+    }
+    loops
+  }
+}
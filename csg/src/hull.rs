@@ -0,0 +1,422 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Convex hulls of points and the oriented bounding boxes fitted to them.
+//!
+//! The hull is built by adding the point furthest out from a face one at a time,
+//! with the exact orient3d predicate deciding which faces a point can see. The
+//! oriented box is fitted to the hull. Its axes are taken from the principal axes of
+//! the hull surface and from the normals of the largest hull faces, for each normal
+//! the box is turned around it to the smallest rectangle. The box with the smallest
+//! volume wins, which is the minimum or close to it.
+
+use {
+  crate::{orient3d, Obb, Pt2, Pt3},
+  std::collections::{HashMap, HashSet},
+};
+
+/// The most hull faces whose normals are tried as an axis of the oriented box.
+const MAX_FACE_CANDIDATES: usize = 256;
+
+struct Face {
+  vertices: [usize; 3],
+  alive: bool,
+  /// The points in front of the face that aren't in front of an earlier face.
+  outside: Vec<usize>,
+}
+
+/// The convex hull of some points.
+///
+/// points: The points.
+///
+/// return: Triangles as indices into points, counter clockwise seen from outside, or
+/// None if the points are all in one plane.
+pub(crate) fn convex_hull(points: &[Pt3]) -> Option<Vec<[usize; 3]>> {
+  let n = points.len();
+  // start with a tetrahedron of points far apart
+  let i0 = (0..n).min_by(|&a, &b| points[a].x.total_cmp(&points[b].x))?;
+  let p0 = points[i0];
+  let i1 = (0..n).max_by(|&a, &b| (points[a] - p0).len2().total_cmp(&(points[b] - p0).len2()))?;
+  let p1 = points[i1];
+  let i2 = (0..n).max_by(|&a, &b| {
+    let area = |i: usize| (points[i] - p0).cross(p1 - p0).len2();
+    area(a).total_cmp(&area(b))
+  })?;
+  let p2 = points[i2];
+  let i3 = (0..n).max_by(|&a, &b| {
+    orient3d(p0, p1, p2, points[a])
+      .abs()
+      .total_cmp(&orient3d(p0, p1, p2, points[b]).abs())
+  })?;
+  let volume = orient3d(p0, p1, p2, points[i3]);
+  if volume == 0.0 {
+    return None;
+  }
+  // the fourth point has to be behind the first face
+  let (i1, i2) = if volume > 0.0 { (i1, i2) } else { (i2, i1) };
+
+  let mut faces: Vec<Face> = [[i0, i1, i2], [i1, i0, i3], [i2, i1, i3], [i0, i2, i3]]
+    .into_iter()
+    .map(|vertices| Face {
+      vertices,
+      alive: true,
+      outside: Vec::new(),
+    })
+    .collect();
+  let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+  for (f, face) in faces.iter().enumerate() {
+    for (a, b) in face_edges(face.vertices) {
+      edges.insert((a, b), f);
+    }
+  }
+  let in_front = |face: [usize; 3], p: usize| {
+    orient3d(points[face[0]], points[face[1]], points[face[2]], points[p]) < 0.0
+  };
+  for p in 0..n {
+    if [i0, i1, i2, i3].contains(&p) {
+      continue;
+    }
+    if let Some(face) = faces.iter_mut().find(|face| in_front(face.vertices, p)) {
+      face.outside.push(p);
+    }
+  }
+
+  let mut pending: Vec<usize> = (0..faces.len()).collect();
+  while let Some(f) = pending.pop() {
+    if !faces[f].alive || faces[f].outside.is_empty() {
+      continue;
+    }
+    let [a, b, c] = faces[f].vertices.map(|v| points[v]);
+    let apex = *faces[f]
+      .outside
+      .iter()
+      .min_by(|&&p, &&q| orient3d(a, b, c, points[p]).total_cmp(&orient3d(a, b, c, points[q])))
+      .unwrap();
+
+    // the faces the apex can see are connected, flood fill from this one
+    let mut visible = vec![f];
+    let mut seen = HashSet::from([f]);
+    let mut horizon = Vec::new();
+    let mut i = 0;
+    while i < visible.len() {
+      for (u, v) in face_edges(faces[visible[i]].vertices) {
+        let neighbour = edges[&(v, u)];
+        if seen.contains(&neighbour) {
+          continue;
+        }
+        if in_front(faces[neighbour].vertices, apex) {
+          seen.insert(neighbour);
+          visible.push(neighbour);
+        } else {
+          horizon.push((u, v));
+        }
+      }
+      i += 1;
+    }
+    // an edge found before its neighbour turned out visible isn't on the horizon
+    horizon.retain(|&(u, v)| !seen.contains(&edges[&(v, u)]));
+
+    let mut orphans = Vec::new();
+    for &g in &visible {
+      faces[g].alive = false;
+      orphans.append(&mut faces[g].outside);
+      for edge in face_edges(faces[g].vertices) {
+        edges.remove(&edge);
+      }
+    }
+    let first = faces.len();
+    for (u, v) in horizon {
+      let vertices = [u, v, apex];
+      for edge in face_edges(vertices) {
+        edges.insert(edge, faces.len());
+      }
+      faces.push(Face {
+        vertices,
+        alive: true,
+        outside: Vec::new(),
+      });
+    }
+    for p in orphans {
+      if p == apex {
+        continue;
+      }
+      if let Some(face) = faces[first..]
+        .iter_mut()
+        .find(|face| in_front(face.vertices, p))
+      {
+        face.outside.push(p);
+      }
+    }
+    pending.extend(first..faces.len());
+  }
+
+  Some(
+    faces
+      .into_iter()
+      .filter(|face| face.alive)
+      .map(|face| face.vertices)
+      .collect(),
+  )
+}
+
+fn face_edges([a, b, c]: [usize; 3]) -> [(usize, usize); 3] {
+  [(a, b), (b, c), (c, a)]
+}
+
+/// Fit an oriented box to some points.
+///
+/// points: The points.
+///
+/// return: The box or None if there are no points.
+pub(crate) fn oriented_bounding_box(points: &[Pt3]) -> Option<Obb> {
+  if points.is_empty() {
+    return None;
+  }
+  let candidates = match convex_hull(points) {
+    Some(faces) => {
+      let mut used: Vec<usize> = faces.iter().flatten().copied().collect();
+      used.sort_unstable();
+      used.dedup();
+      let hull_points: Vec<Pt3> = used.into_iter().map(|v| points[v]).collect();
+
+      // the covariance of the hull surface, which doesn't depend on how the points
+      // are spread over it
+      let mut area = 0.0;
+      let mut mean = Pt3::new(0.0, 0.0, 0.0);
+      let mut moments = [[0.0; 3]; 3];
+      for face in &faces {
+        let [a, b, c] = face.map(|v| points[v]);
+        let weight = (b - a).cross(c - a).len() * 0.5;
+        let centroid = (a + b + c) / 3.0;
+        area += weight;
+        mean += centroid * weight;
+        for i in 0..3 {
+          for j in 0..3 {
+            moments[i][j] += weight / 12.0
+              * (9.0 * centroid[i] * centroid[j] + a[i] * a[j] + b[i] * b[j] + c[i] * c[j]);
+          }
+        }
+      }
+      mean /= area;
+      let mut covariance = moments;
+      for (i, row) in covariance.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+          *value = *value / area - mean[i] * mean[j];
+        }
+      }
+      let mut candidates = vec![principal_axes(covariance)];
+
+      // the normals of the largest faces, each one once
+      let mut normals: Vec<Pt3> = faces
+        .iter()
+        .map(|face| {
+          let [a, b, c] = face.map(|v| points[v]);
+          (b - a).cross(c - a)
+        })
+        .filter(|normal| normal.len2() > 0.0)
+        .collect();
+      normals.sort_by(|a, b| b.len2().total_cmp(&a.len2()));
+      let mut seen = HashSet::new();
+      for normal in normals {
+        let normal = normal.normalized();
+        let key = [normal.x, normal.y, normal.z].map(|v| (v * 1.0e6).round() as i64);
+        if seen.insert(key) {
+          candidates.push(turned_to_fit(normal, &hull_points));
+          if seen.len() == MAX_FACE_CANDIDATES {
+            break;
+          }
+        }
+      }
+      (candidates, hull_points)
+    }
+    None => {
+      // flat or a line, fit the box in the plane of the points
+      let n = points.len() as f64;
+      let mut mean = Pt3::new(0.0, 0.0, 0.0);
+      for p in points {
+        mean += *p;
+      }
+      mean /= n;
+      let mut covariance = [[0.0; 3]; 3];
+      for p in points {
+        let d = *p - mean;
+        for i in 0..3 {
+          for j in 0..3 {
+            covariance[i][j] += d[i] * d[j] / n;
+          }
+        }
+      }
+      let axes = principal_axes(covariance);
+      (vec![axes, turned_to_fit(axes[2], points)], points.to_vec())
+    }
+  };
+
+  let (candidates, hull_points) = candidates;
+  candidates
+    .into_iter()
+    .map(|axes| fit(axes, &hull_points))
+    .min_by(|a, b| a.volume().total_cmp(&b.volume()))
+}
+
+/// The box with the given axes around the points.
+fn fit(axes: [Pt3; 3], points: &[Pt3]) -> Obb {
+  let mut min = [f64::INFINITY; 3];
+  let mut max = [f64::NEG_INFINITY; 3];
+  for p in points {
+    for k in 0..3 {
+      let d = p.dot(axes[k]);
+      min[k] = min[k].min(d);
+      max[k] = max[k].max(d);
+    }
+  }
+  let mut center = Pt3::new(0.0, 0.0, 0.0);
+  for k in 0..3 {
+    center += axes[k] * ((min[k] + max[k]) / 2.0);
+  }
+  Obb {
+    center,
+    axes,
+    half_size: Pt3::new(
+      (max[0] - min[0]) / 2.0,
+      (max[1] - min[1]) / 2.0,
+      (max[2] - min[2]) / 2.0,
+    ),
+  }
+}
+
+/// Axes with the third one along normal and the other two turned around it so the
+/// rectangle around the points seen along the normal is as small as possible.
+fn turned_to_fit(normal: Pt3, points: &[Pt3]) -> [Pt3; 3] {
+  let helper = if normal.x.abs() < 0.9 {
+    Pt3::new(1.0, 0.0, 0.0)
+  } else {
+    Pt3::new(0.0, 1.0, 0.0)
+  };
+  let u = helper.cross(normal).normalized();
+  let v = normal.cross(u);
+  let flat: Vec<Pt2> = points
+    .iter()
+    .map(|p| Pt2::new(p.dot(u), p.dot(v)))
+    .collect();
+  let outline = convex_hull2d(flat);
+
+  // one side of the smallest rectangle lies along an edge of the outline
+  let mut best = (f64::INFINITY, Pt2::new(1.0, 0.0));
+  for i in 0..outline.len() {
+    let edge = outline[(i + 1) % outline.len()] - outline[i];
+    if edge.len2() == 0.0 {
+      continue;
+    }
+    let dir = edge / edge.len();
+    let perp = Pt2::new(-dir.y, dir.x);
+    let (mut lo, mut hi, mut low, mut high) = (
+      f64::INFINITY,
+      f64::NEG_INFINITY,
+      f64::INFINITY,
+      f64::NEG_INFINITY,
+    );
+    for p in &outline {
+      lo = lo.min(p.dot(dir));
+      hi = hi.max(p.dot(dir));
+      low = low.min(p.dot(perp));
+      high = high.max(p.dot(perp));
+    }
+    let area = (hi - lo) * (high - low);
+    if area < best.0 {
+      best = (area, dir);
+    }
+  }
+  let dir = best.1;
+  let x = u * dir.x + v * dir.y;
+  [x, normal.cross(x), normal]
+}
+
+/// The convex hull of points in the plane, counter clockwise, by the monotone chain.
+fn convex_hull2d(mut points: Vec<Pt2>) -> Vec<Pt2> {
+  points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+  points.dedup();
+  if points.len() < 3 {
+    return points;
+  }
+  let turn = |o: Pt2, a: Pt2, b: Pt2| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+  let mut hull: Vec<Pt2> = Vec::with_capacity(points.len() + 1);
+  for pass in 0..2 {
+    let start = hull.len();
+    let ordered: Box<dyn Iterator<Item = &Pt2>> = if pass == 0 {
+      Box::new(points.iter())
+    } else {
+      Box::new(points.iter().rev())
+    };
+    for &p in ordered {
+      while hull.len() >= start + 2 && turn(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+        hull.pop();
+      }
+      hull.push(p);
+    }
+    // the last point of each chain is the first of the other
+    hull.pop();
+  }
+  hull
+}
+
+/// The eigenvectors of a symmetric matrix by Jacobi rotations, as right handed axes
+/// from the largest eigenvalue to the smallest.
+fn principal_axes(mut m: [[f64; 3]; 3]) -> [Pt3; 3] {
+  let mut vectors = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+  for _ in 0..50 {
+    let off = m[0][1].abs() + m[0][2].abs() + m[1][2].abs();
+    if off <= 1.0e-15 * (m[0][0].abs() + m[1][1].abs() + m[2][2].abs()) {
+      break;
+    }
+    for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+      if m[p][q] == 0.0 {
+        continue;
+      }
+      let theta = (m[q][q] - m[p][p]) / (2.0 * m[p][q]);
+      let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+      let c = 1.0 / (t * t + 1.0).sqrt();
+      let s = t * c;
+      // m = J^T m J with the rotation J in the p, q plane
+      for row in m.iter_mut() {
+        let (a, b) = (row[p], row[q]);
+        row[p] = c * a - s * b;
+        row[q] = s * a + c * b;
+      }
+      let (row_p, row_q) = (m[p], m[q]);
+      for (k, (a, b)) in row_p.into_iter().zip(row_q).enumerate() {
+        m[p][k] = c * a - s * b;
+        m[q][k] = s * a + c * b;
+      }
+      for row in vectors.iter_mut() {
+        let (a, b) = (row[p], row[q]);
+        row[p] = c * a - s * b;
+        row[q] = s * a + c * b;
+      }
+    }
+  }
+  let mut order = [0, 1, 2];
+  order.sort_by(|&a, &b| m[b][b].total_cmp(&m[a][a]));
+  let column = |k: usize| Pt3::new(vectors[0][k], vectors[1][k], vectors[2][k]).normalized();
+  let x = column(order[0]);
+  let y = column(order[1]);
+  [x, y, x.cross(y)]
+}
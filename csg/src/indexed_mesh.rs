@@ -0,0 +1,152 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A mesh that stores each vertex once.
+//!
+//! Mesh keeps three copies of a point for every triangle that uses it, which is
+//! simple but makes any question about connectivity a search. IndexedMesh keeps a
+//! vertex buffer and triangles that index into it. The vertices are shared through a
+//! spatial hash so building one is linear in the number of triangles.
+
+use {
+  crate::{weld::Welder, Mesh, Pt3, Triangle, CSG},
+  std::collections::HashSet,
+};
+
+/// A mesh with shared vertices.
+#[derive(Clone, Default)]
+pub struct IndexedMesh {
+  pub vertices: Vec<Pt3>,
+  /// Indices into vertices, counter clockwise when looking at the front.
+  pub triangles: Vec<[usize; 3]>,
+}
+
+impl IndexedMesh {
+  /// Creates an indexed mesh from a list of triangles, points that are exactly equal
+  /// become one vertex.
+  ///
+  /// triangles: The triangles.
+  ///
+  /// return: The indexed mesh.
+  pub fn from_triangles(triangles: &[Triangle]) -> Self {
+    Self::build(triangles, 0.0)
+  }
+
+  /// Creates an indexed mesh from a list of triangles, welding points that are
+  /// within tolerance of each other into one vertex. Triangles that collapse are
+  /// removed.
+  ///
+  /// triangles: The triangles.
+  ///
+  /// tolerance: How close points have to be to weld.
+  ///
+  /// return: The indexed mesh.
+  pub fn from_triangles_welded(triangles: &[Triangle], tolerance: f64) -> Self {
+    let mut mesh = Self::build(triangles, tolerance);
+    mesh
+      .triangles
+      .retain(|[a, b, c]| a != b && b != c && c != a);
+    mesh
+  }
+
+  /// Creates an indexed mesh from a mesh, points that are exactly equal become one
+  /// vertex.
+  ///
+  /// mesh: The mesh.
+  ///
+  /// return: The indexed mesh.
+  pub fn from_mesh(mesh: &Mesh) -> Self {
+    Self::from_triangles(&mesh.triangles)
+  }
+
+  /// Creates an indexed mesh from a CSG object.
+  ///
+  /// csg: The CSG object.
+  ///
+  /// return: The indexed mesh.
+  pub fn from_csg(csg: CSG) -> Self {
+    Self::from_triangles(&csg.into_triangles())
+  }
+
+  /// Convert to a mesh of triangles.
+  ///
+  /// return: The mesh.
+  pub fn to_mesh(&self) -> Mesh {
+    Mesh::from_triangles(
+      self
+        .triangles
+        .iter()
+        .map(|&[a, b, c]| Triangle::new(self.vertices[a], self.vertices[b], self.vertices[c]))
+        .collect(),
+    )
+  }
+
+  /// Convert to a CSG object.
+  ///
+  /// return: The CSG object.
+  pub fn to_csg(&self) -> CSG {
+    CSG::from_mesh(self.to_mesh())
+  }
+
+  /// Return the unique edges as pairs of vertex indices, in the direction they are
+  /// first used by a triangle.
+  pub fn edges(&self) -> Vec<[usize; 2]> {
+    let mut seen = HashSet::with_capacity(self.triangles.len() * 2);
+    let mut edges = Vec::with_capacity(self.triangles.len() * 3 / 2);
+    for &[a, b, c] in &self.triangles {
+      for (p, q) in [(a, b), (b, c), (c, a)] {
+        if seen.insert((p.min(q), p.max(q))) {
+          edges.push([p, q]);
+        }
+      }
+    }
+    edges
+  }
+
+  fn build(triangles: &[Triangle], tolerance: f64) -> Self {
+    let mut welder = Welder::new(tolerance);
+    let mut indices = Vec::with_capacity(triangles.len());
+    for triangle in triangles {
+      indices.push([
+        welder.index(triangle.a),
+        welder.index(triangle.b),
+        welder.index(triangle.c),
+      ]);
+    }
+    Self {
+      vertices: welder.into_vertices(),
+      triangles: indices,
+    }
+  }
+}
+
+impl From<Mesh> for IndexedMesh {
+  fn from(mesh: Mesh) -> Self {
+    Self::from_mesh(&mesh)
+  }
+}
+
+impl From<IndexedMesh> for Mesh {
+  fn from(mesh: IndexedMesh) -> Self {
+    mesh.to_mesh()
+  }
+}
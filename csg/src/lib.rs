@@ -20,21 +20,44 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod cache;
 mod csg;
 mod ear_clip;
+mod error;
+mod half_edge;
+mod hull;
+mod indexed_mesh;
+mod mass;
+mod merge;
 mod mesh;
+mod obj;
+mod repair;
+mod shape;
+mod stl;
+mod three_mf;
 mod triangle;
+mod validation;
 mod viewer;
+mod weld;
+mod zip;
 
 pub use {
-  csg::{BSPNode, Plane, Polygon, CSG},
+  cache::Cache,
+  csg::{BSPNode, CsgOptions, Plane, Polygon, Precision, SplitStrategy, Tolerance, CSG},
   csg_math::{
-    approx_eq, dacos, dasin, datan, dcos, dsin, dtan, CubicBezier2D, CubicBezier3D,
-    CubicBezierChain2D, CubicBezierChain3D, MersenneTwister, Mt4, Pt2, Pt3, Pt4, QuadraticBezier2D,
-    QuadraticBezier3D, VecPt2, VecPt3,
+    approx_eq, dacos, dasin, datan, dcos, dsin, dtan, orient3d, orient3d_exact, Aabb,
+    CubicBezier2D, CubicBezier3D, CubicBezierChain2D, CubicBezierChain3D, MersenneTwister, Mt4,
+    Obb, Pt2, Pt3, Pt4, QuadraticBezier2D, QuadraticBezier3D, Quat, VecPt2, VecPt3,
   },
   ear_clip::{triangulate2d, triangulate3d},
+  error::{Error, Result},
+  half_edge::{HalfEdge, HalfEdgeMesh, TopologyError},
+  indexed_mesh::IndexedMesh,
+  mass::MassProperties,
   mesh::Mesh,
+  shape::Shape,
+  three_mf::{Assembly, Part, Unit},
   triangle::{Triangle, VecTriangle},
+  validation::ValidationReport,
   viewer::Viewer,
 };
@@ -0,0 +1,178 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Volume, area, center of mass and inertia of closed meshes.
+//!
+//! The volume integrals are turned into sums over the triangles with the divergence
+//! theorem, following "Polyhedral Mass Properties (Revisited)" by David Eberly. The
+//! results are exact for any closed mesh with consistent winding, a sphere made of
+//! triangles is a polyhedron a little smaller than the sphere.
+
+use crate::{Mesh, Pt3};
+
+/// The mass properties of a solid with uniform density.
+#[derive(Clone, Copy, Debug)]
+pub struct MassProperties {
+  /// The volume, negative if the normals point in.
+  pub volume: f64,
+  /// The surface area.
+  pub area: f64,
+  /// The volume times the density.
+  pub mass: f64,
+  /// The center of mass.
+  pub center_of_mass: Pt3,
+  /// The inertia tensor about the center of mass, rows and columns are x, y and z.
+  pub inertia: [[f64; 3]; 3],
+}
+
+impl MassProperties {
+  /// Compute the mass properties of a closed mesh.
+  ///
+  /// mesh: The mesh.
+  ///
+  /// density: The mass per unit of volume.
+  ///
+  /// return: The mass properties.
+  pub fn new(mesh: &Mesh, density: f64) -> Self {
+    // integrate relative to a vertex to keep the precision for models far from the
+    // origin
+    let origin = mesh
+      .triangles
+      .first()
+      .map_or(Pt3::new(0.0, 0.0, 0.0), |t| t.a);
+    // 1, x, y, z, x^2, y^2, z^2, xy, yz, zx
+    let mut integrals = [0.0; 10];
+    let mut area = 0.0;
+    for triangle in &mesh.triangles {
+      let p0 = triangle.a - origin;
+      let p1 = triangle.b - origin;
+      let p2 = triangle.c - origin;
+      let d = (p1 - p0).cross(p2 - p0);
+      area += d.len() * 0.5;
+      let (f1x, f2x, f3x, g0x, g1x, g2x) = subexpressions(p0.x, p1.x, p2.x);
+      let (_, f2y, f3y, g0y, g1y, g2y) = subexpressions(p0.y, p1.y, p2.y);
+      let (_, f2z, f3z, g0z, g1z, g2z) = subexpressions(p0.z, p1.z, p2.z);
+      integrals[0] += d.x * f1x;
+      integrals[1] += d.x * f2x;
+      integrals[2] += d.y * f2y;
+      integrals[3] += d.z * f2z;
+      integrals[4] += d.x * f3x;
+      integrals[5] += d.y * f3y;
+      integrals[6] += d.z * f3z;
+      integrals[7] += d.x * (p0.y * g0x + p1.y * g1x + p2.y * g2x);
+      integrals[8] += d.y * (p0.z * g0y + p1.z * g1y + p2.z * g2y);
+      integrals[9] += d.z * (p0.x * g0z + p1.x * g1z + p2.x * g2z);
+    }
+    let weights = [
+      1.0 / 6.0,
+      1.0 / 24.0,
+      1.0 / 24.0,
+      1.0 / 24.0,
+      1.0 / 60.0,
+      1.0 / 60.0,
+      1.0 / 60.0,
+      1.0 / 120.0,
+      1.0 / 120.0,
+      1.0 / 120.0,
+    ];
+    for (integral, weight) in integrals.iter_mut().zip(weights) {
+      *integral *= weight;
+    }
+
+    let volume = integrals[0];
+    let c = if volume == 0.0 {
+      Pt3::new(0.0, 0.0, 0.0)
+    } else {
+      Pt3::new(integrals[1], integrals[2], integrals[3]) / volume
+    };
+    // the second moments about the origin moved to the center of mass
+    let xx = integrals[5] + integrals[6] - volume * (c.y * c.y + c.z * c.z);
+    let yy = integrals[4] + integrals[6] - volume * (c.z * c.z + c.x * c.x);
+    let zz = integrals[4] + integrals[5] - volume * (c.x * c.x + c.y * c.y);
+    let xy = -(integrals[7] - volume * c.x * c.y);
+    let yz = -(integrals[8] - volume * c.y * c.z);
+    let zx = -(integrals[9] - volume * c.z * c.x);
+    let inertia = [[xx, xy, zx], [xy, yy, yz], [zx, yz, zz]].map(|row| row.map(|v| v * density));
+
+    Self {
+      volume,
+      area,
+      mass: volume * density,
+      center_of_mass: c + origin,
+      inertia,
+    }
+  }
+
+  /// The inertia tensor about a point other than the center of mass, by the parallel
+  /// axis theorem.
+  ///
+  /// point: The point.
+  ///
+  /// return: The inertia tensor.
+  pub fn inertia_about_point(&self, point: Pt3) -> [[f64; 3]; 3] {
+    let r = self.center_of_mass - point;
+    let r = [r.x, r.y, r.z];
+    let r2 = r[0] * r[0] + r[1] * r[1] + r[2] * r[2];
+    let mut inertia = self.inertia;
+    for (i, row) in inertia.iter_mut().enumerate() {
+      for (j, value) in row.iter_mut().enumerate() {
+        let identity = if i == j { 1.0 } else { 0.0 };
+        *value += self.mass * (r2 * identity - r[i] * r[j]);
+      }
+    }
+    inertia
+  }
+
+  /// The moment of inertia about an axis.
+  ///
+  /// point: A point on the axis.
+  ///
+  /// direction: The direction of the axis, doesn't need to be normalized.
+  ///
+  /// return: The moment of inertia.
+  pub fn inertia_about_axis(&self, point: Pt3, direction: Pt3) -> f64 {
+    let d = direction.normalized();
+    let d = [d.x, d.y, d.z];
+    let inertia = self.inertia_about_point(point);
+    let mut moment = 0.0;
+    for i in 0..3 {
+      for j in 0..3 {
+        moment += d[i] * inertia[i][j] * d[j];
+      }
+    }
+    moment
+  }
+}
+
+/// The sums of products of the coordinates of a triangle the integrals are made of.
+fn subexpressions(w0: f64, w1: f64, w2: f64) -> (f64, f64, f64, f64, f64, f64) {
+  let temp0 = w0 + w1;
+  let f1 = temp0 + w2;
+  let temp1 = w0 * w0;
+  let temp2 = temp1 + w1 * temp0;
+  let f2 = temp2 + w2 * f1;
+  let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+  let g0 = f2 + w0 * (f1 + w0);
+  let g1 = f2 + w1 * (f1 + w1);
+  let g2 = f2 + w2 * (f1 + w2);
+  (f1, f2, f3, g0, g1, g2)
+}
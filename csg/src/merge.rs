@@ -0,0 +1,303 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Merging of coplanar polygons.
+//!
+//! The BSP tree cuts a flat face into many fragments. Fragments that lie in the same
+//! plane and share edges are merged into one region by cancelling the edges they
+//! share, the boundary of the region is split into outlines and holes, the holes
+//! are bridged to the outlines and the result is triangulated with ear clipping.
+//! Every vertex on the boundary is kept so the faces around the region still line up
+//! with it. A region that can't be merged cleanly, like one whose outlines touch at
+//! a vertex, keeps its original fragments.
+
+use {
+  crate::{
+    ear_clip::{cross, project, try_triangulate3d},
+    weld::vertex_key,
+    Polygon, Pt2, Pt3,
+  },
+  std::collections::{HashMap, HashSet},
+};
+
+/// How close the normals of two planes have to be to merge their polygons.
+const NORMAL_QUANTUM: f64 = 1.0e-6;
+/// How close the distances of two planes from the origin have to be.
+const DISTANCE_QUANTUM: f64 = 1.0e-5;
+/// How far from a bridge, relative to its length, a point counts as on it.
+const SINE_EPSILON: f64 = 1.0e-10;
+
+/// Merge coplanar polygons that share edges and triangulate the merged regions.
+///
+/// polygons: Welded polygons without T-junctions, like the result of a boolean.
+///
+/// return: The triangles of the merged regions and the polygons that weren't merged.
+pub(crate) fn merge_coplanar(polygons: Vec<Polygon>) -> Vec<Polygon> {
+  let mut groups: HashMap<(i64, i64, i64, i64), Vec<Polygon>> = HashMap::new();
+  let mut order = Vec::new();
+  for polygon in polygons {
+    let n = polygon.plane.normal;
+    let key = (
+      (n.x / NORMAL_QUANTUM).round() as i64,
+      (n.y / NORMAL_QUANTUM).round() as i64,
+      (n.z / NORMAL_QUANTUM).round() as i64,
+      (polygon.plane.w / DISTANCE_QUANTUM).round() as i64,
+    );
+    let group = groups.entry(key).or_default();
+    if group.is_empty() {
+      order.push(key);
+    }
+    group.push(polygon);
+  }
+
+  let mut result = Vec::new();
+  // go through the groups in the order they were found so the output is repeatable
+  for key in order {
+    let group = groups.remove(&key).unwrap();
+    if group.len() < 2 {
+      result.extend(group);
+      continue;
+    }
+    match merge_group(&group) {
+      Some(mut triangles) => result.append(&mut triangles),
+      None => result.extend(group),
+    }
+  }
+  result
+}
+
+/// Merge the polygons of one plane.
+///
+/// return: The triangles or None if the polygons can't be merged cleanly.
+fn merge_group(group: &[Polygon]) -> Option<Vec<Polygon>> {
+  let normal = group[0].plane.normal;
+  let mut vertices: Vec<Pt3> = Vec::new();
+  let mut index: HashMap<[u64; 3], usize> = HashMap::new();
+  let mut edges: HashSet<(usize, usize)> = HashSet::new();
+  for polygon in group {
+    let n = polygon.vertices.len();
+    let ids: Vec<usize> = polygon
+      .vertices
+      .iter()
+      .map(|v| {
+        *index.entry(vertex_key(*v)).or_insert_with(|| {
+          vertices.push(*v);
+          vertices.len() - 1
+        })
+      })
+      .collect();
+    for i in 0..n {
+      let (a, b) = (ids[i], ids[(i + 1) % n]);
+      if a == b {
+        continue;
+      }
+      // an edge shared with a neighbour is inside the region
+      if !edges.remove(&(b, a)) && !edges.insert((a, b)) {
+        // overlapping polygons
+        return None;
+      }
+    }
+  }
+
+  // each boundary vertex has to have one way out or the loops are ambiguous
+  let mut next: HashMap<usize, usize> = HashMap::with_capacity(edges.len());
+  for &(a, b) in &edges {
+    if next.insert(a, b).is_some() {
+      return None;
+    }
+  }
+
+  let points: Vec<Pt2> = vertices.iter().map(|v| project(*v, normal)).collect();
+  let mut starts: Vec<usize> = next.keys().copied().collect();
+  starts.sort_unstable();
+  let mut visited = vec![false; vertices.len()];
+  let mut outlines: Vec<(Vec<usize>, f64)> = Vec::new();
+  let mut holes: Vec<Vec<usize>> = Vec::new();
+  for start in starts {
+    if visited[start] {
+      continue;
+    }
+    let mut ring = Vec::new();
+    let mut v = start;
+    while !visited[v] {
+      visited[v] = true;
+      ring.push(v);
+      v = *next.get(&v)?;
+    }
+    if v != start {
+      return None;
+    }
+    let area = signed_area(&ring, &points);
+    if area > 0.0 {
+      outlines.push((ring, area));
+    } else if area < 0.0 {
+      holes.push(ring);
+    }
+  }
+
+  // put each hole in the smallest outline around it
+  let mut outline_holes: Vec<Vec<Vec<usize>>> = vec![Vec::new(); outlines.len()];
+  for hole in holes {
+    let p = points[hole[0]];
+    let owner = outlines
+      .iter()
+      .enumerate()
+      .filter(|(_, (ring, _))| contains(ring, &points, p))
+      .min_by(|a, b| a.1 .1.total_cmp(&b.1 .1))?
+      .0;
+    outline_holes[owner].push(hole);
+  }
+
+  let mut triangles = Vec::new();
+  for ((outline, _), holes) in outlines.into_iter().zip(outline_holes) {
+    let ring = bridge_holes(outline, holes, &points)?;
+    let corners: Vec<Pt3> = ring.iter().map(|&i| vertices[i]).collect();
+    let indices = try_triangulate3d(&corners, normal)?;
+    // triangles that overlap cover more than the region does
+    let area = signed_area(&ring, &points);
+    let covered: f64 = indices
+      .chunks(3)
+      .map(|t| cross(points[ring[t[0]]], points[ring[t[1]]], points[ring[t[2]]]).abs())
+      .sum();
+    if covered * 0.5 - area > 1.0e-6 * area {
+      return None;
+    }
+    for t in indices.chunks(3) {
+      triangles.push(Polygon::new(vec![
+        corners[t[0]],
+        corners[t[1]],
+        corners[t[2]],
+      ]));
+    }
+  }
+  Some(triangles)
+}
+
+fn signed_area(ring: &[usize], points: &[Pt2]) -> f64 {
+  let n = ring.len();
+  let mut area = 0.0;
+  for i in 0..n {
+    let a = points[ring[i]];
+    let b = points[ring[(i + 1) % n]];
+    area += a.x * b.y - b.x * a.y;
+  }
+  area * 0.5
+}
+
+/// Even-odd test of a point against a ring.
+fn contains(ring: &[usize], points: &[Pt2], p: Pt2) -> bool {
+  let n = ring.len();
+  let mut inside = false;
+  for i in 0..n {
+    let a = points[ring[i]];
+    let b = points[ring[(i + 1) % n]];
+    if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+      inside = !inside;
+    }
+  }
+  inside
+}
+
+/// Whether the edge cd blocks a bridge from a to b. It does if they cross or if an
+/// end of the edge is on the bridge, the ends of the bridge itself don't count.
+fn blocks(a: Pt2, b: Pt2, c: Pt2, d: Pt2) -> bool {
+  if a == c || a == d || b == c || b == d {
+    return false;
+  }
+  let d1 = cross(a, b, c);
+  let d2 = cross(a, b, d);
+  let d3 = cross(c, d, a);
+  let d4 = cross(c, d, b);
+  if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+    && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+  {
+    return true;
+  }
+  // A point a hair off the bridge is on it, a bridge that grazes a vertex leaves a
+  // ring that folds back on itself.
+  let ab = b - a;
+  let on_bridge = |p: Pt2| {
+    let t = (p - a).dot(ab);
+    t > 0.0 && t < ab.len2() && cross(a, b, p).abs() <= SINE_EPSILON * ab.len2()
+  };
+  on_bridge(c) || on_bridge(d)
+}
+
+/// Join the holes to the outline with pairs of edges so the region is one ring.
+///
+/// return: The ring or None if a hole can't see the outline.
+fn bridge_holes(
+  outline: Vec<usize>,
+  mut holes: Vec<Vec<usize>>,
+  points: &[Pt2],
+) -> Option<Vec<usize>> {
+  // Start with the hole furthest right, it can always see the outline.
+  let rightmost = |hole: &Vec<usize>| {
+    (0..hole.len())
+      .max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x))
+      .unwrap()
+  };
+  holes.sort_by(|a, b| {
+    points[b[rightmost(b)]]
+      .x
+      .total_cmp(&points[a[rightmost(a)]].x)
+  });
+
+  let mut ring = outline;
+  for h in 0..holes.len() {
+    let hole = &holes[h];
+    let start = rightmost(hole);
+    let m = points[hole[start]];
+    // try the vertices of the ring from the closest out
+    let mut candidates: Vec<usize> = (0..ring.len()).collect();
+    candidates.sort_by(|&a, &b| {
+      (points[ring[a]] - m)
+        .len2()
+        .total_cmp(&(points[ring[b]] - m).len2())
+    });
+    let blocked = |p: Pt2, edges: &[usize]| {
+      (0..edges.len()).any(|i| blocks(m, p, points[edges[i]], points[edges[(i + 1) % edges.len()]]))
+    };
+    let bridge = candidates.into_iter().find(|&i| {
+      let p = points[ring[i]];
+      // the bridge has to leave the ring into the region
+      let n = ring.len();
+      let prev = points[ring[(i + n - 1) % n]];
+      let next = points[ring[(i + 1) % n]];
+      let inside = if cross(prev, p, next) >= 0.0 {
+        cross(p, next, m) > 0.0 && cross(prev, p, m) > 0.0
+      } else {
+        cross(p, next, m) > 0.0 || cross(prev, p, m) > 0.0
+      };
+      inside && !blocked(p, &ring) && !holes[h..].iter().any(|other| blocked(p, other))
+    })?;
+    // ring[..=bridge], hole from start around to start, back to ring[bridge], rest
+    let mut joined = Vec::with_capacity(ring.len() + hole.len() + 2);
+    joined.extend_from_slice(&ring[..=bridge]);
+    for k in 0..=hole.len() {
+      joined.push(hole[(start + k) % hole.len()]);
+    }
+    joined.extend_from_slice(&ring[bridge..]);
+    ring = joined;
+  }
+  Some(ring)
+}
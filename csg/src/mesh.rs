@@ -26,8 +26,16 @@
 //! '-' for difference, and '*' for intersection.
 
 use {
-  crate::{dcos, dsin, triangulate3d, Mt4, Pt2, Pt3, Triangle, VecPt2, VecPt3, VecTriangle, CSG},
-  std::io::{Read, Write},
+  crate::{
+    dcos, dsin, hull::oriented_bounding_box, obj, repair::repair, stl, triangulate3d, Aabb,
+    IndexedMesh, MassProperties, Mt4, Obb, Pt2, Pt3, Result, Triangle, ValidationReport, VecPt2,
+    VecPt3, VecTriangle, CSG,
+  },
+  std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+  },
 };
 
 /// A mesh composed of triangles.
@@ -55,6 +63,121 @@ impl Mesh {
     Self::from_triangles(csg.into_triangles())
   }
 
+  /// Merge coplanar triangles that share edges and triangulate the merged regions
+  /// again, which cleans up the many small triangles boolean operations leave on
+  /// flat faces.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn merge_coplanar(&mut self) -> &mut Self {
+    let mut csg = CSG::from_triangles(std::mem::take(&mut self.triangles));
+    csg.merge_coplanar();
+    self.triangles = csg.into_triangles();
+    self
+  }
+
+  /// Weld vertices that are within tolerance of each other, remove triangles with no
+  /// area and split edges at T-junctions, so a mesh that should be closed is edge
+  /// manifold again.
+  ///
+  /// tolerance: How close vertices have to be to weld and how thin a triangle has to
+  /// be to remove. Must be greater than zero.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn clean(&mut self, tolerance: f64) -> &mut Self {
+    let mut csg = CSG::from_triangles(std::mem::take(&mut self.triangles));
+    csg.clean(tolerance);
+    self.triangles = csg.into_triangles();
+    self
+  }
+
+  /// Check the mesh for open edges, non-manifold edges and vertices, flipped
+  /// triangles, flat triangles and triangles that cut through each other.
+  ///
+  /// return: The report of the problems found.
+  pub fn validate(&self) -> ValidationReport {
+    ValidationReport::new(self)
+  }
+
+  /// Repair a broken mesh, like an STL with flipped facets and small gaps. Duplicate
+  /// triangles are removed, the winding is made consistent across every connected
+  /// piece, holes with up to 64 edges are filled and the pieces are turned so their
+  /// normals point out.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn repair(&mut self) -> &mut Self {
+    self.repair_with(64)
+  }
+
+  /// Repair a broken mesh like [`Mesh::repair`], with a limit on the holes to fill.
+  ///
+  /// max_hole_edges: Holes with more edges than this are left open.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn repair_with(&mut self, max_hole_edges: usize) -> &mut Self {
+    self.triangles = repair(&self.triangles, max_hole_edges);
+    self
+  }
+
+  /// The axis aligned box around the mesh.
+  ///
+  /// return: The box or None if the mesh has no triangles.
+  pub fn bounding_box(&self) -> Option<Aabb> {
+    Aabb::from_points(self.triangles.iter().flat_map(|t| [t.a, t.b, t.c]))
+  }
+
+  /// A tight box around the mesh that can be turned any way. The box is fitted to the
+  /// convex hull of the mesh, it has the smallest volume or is close to it.
+  ///
+  /// return: The box or None if the mesh has no triangles.
+  pub fn oriented_bounding_box(&self) -> Option<Obb> {
+    oriented_bounding_box(&self.vertices())
+  }
+
+  /// The volume enclosed by a closed mesh.
+  ///
+  /// return: The volume, negative if the normals point in.
+  pub fn volume(&self) -> f64 {
+    MassProperties::new(self, 1.0).volume
+  }
+
+  /// The total area of the triangles.
+  pub fn surface_area(&self) -> f64 {
+    MassProperties::new(self, 1.0).area
+  }
+
+  /// The volume, area, mass, center of mass and inertia of a closed mesh.
+  ///
+  /// density: The mass per unit of volume.
+  ///
+  /// return: The mass properties.
+  pub fn mass_properties(&self, density: f64) -> MassProperties {
+    MassProperties::new(self, density)
+  }
+
+  /// Union of any number of meshes, faster than adding them one at a time.
+  ///
+  /// meshes: The meshes to join.
+  ///
+  /// return: The union of the meshes.
+  pub fn union_all(meshes: impl IntoIterator<Item = Mesh>) -> Self {
+    Self::from_csg(CSG::union_all(meshes.into_iter().map(CSG::from_mesh)))
+  }
+
+  /// Subtract any number of meshes from a mesh, faster than subtracting them one
+  /// at a time.
+  ///
+  /// base: The mesh to cut.
+  ///
+  /// tools: The meshes to cut away.
+  ///
+  /// return: The base with the tools removed.
+  pub fn subtract_all(base: Mesh, tools: impl IntoIterator<Item = Mesh>) -> Self {
+    Self::from_csg(CSG::subtract_all(
+      CSG::from_mesh(base),
+      tools.into_iter().map(CSG::from_mesh),
+    ))
+  }
+
   /// Creates a mesh from a list of vertices and an index that specifies
   /// the triagles.
   ///
@@ -78,69 +201,17 @@ impl Mesh {
 
   /// Return an array of the unique vertices in a mesh.
   pub fn vertices(&self) -> Vec<Pt3> {
-    let mut points: Vec<Pt3> = Vec::new();
-    for triangle in &self.triangles {
-      let mut a_found = false;
-      let mut b_found = false;
-      let mut c_found = false;
-      for point in &points {
-        if triangle.a == *point {
-          a_found = true;
-        }
-        if triangle.b == *point {
-          b_found = true;
-        }
-        if triangle.c == *point {
-          c_found = true;
-        }
-      }
-      if !a_found {
-        points.push(triangle.a);
-      }
-      if !b_found {
-        points.push(triangle.b);
-      }
-      if !c_found {
-        points.push(triangle.c);
-      }
-    }
-    points
+    IndexedMesh::from_mesh(self).vertices
   }
 
   /// Return all the unique edges in a mesh
   pub fn edges(&self) -> Vec<(Pt3, Pt3)> {
-    let mut edges: Vec<(Pt3, Pt3)> = Vec::new();
-    for triangle in &self.triangles {
-      let edge1 = (triangle.a, triangle.b);
-      let edge2 = (triangle.b, triangle.c);
-      let edge3 = (triangle.c, triangle.a);
-
-      let mut edge1_found = false;
-      let mut edge2_found = false;
-      let mut edge3_found = false;
-
-      for edge in &edges {
-        if (edge1.0 == edge.0 && edge1.1 == edge.1) || (edge1.1 == edge.0 && edge1.0 == edge.1) {
-          edge1_found = true;
-        }
-        if (edge2.0 == edge.0 && edge2.1 == edge.1) || (edge2.1 == edge.0 && edge2.0 == edge.1) {
-          edge2_found = true;
-        }
-        if (edge3.0 == edge.0 && edge3.1 == edge.1) || (edge3.1 == edge.0 && edge3.0 == edge.1) {
-          edge3_found = true;
-        }
-      }
-      if !edge1_found {
-        edges.push(edge1);
-      }
-      if !edge2_found {
-        edges.push(edge2);
-      }
-      if !edge3_found {
-        edges.push(edge3);
-      }
-    }
-    edges
+    let indexed = IndexedMesh::from_mesh(self);
+    indexed
+      .edges()
+      .into_iter()
+      .map(|[a, b]| (indexed.vertices[a], indexed.vertices[b]))
+      .collect()
   }
 
   /// Translate a mesh by the given vector.
@@ -183,6 +254,63 @@ impl Mesh {
     self
   }
 
+  /// Transform a mesh by an affine matrix. The winding is reversed if the matrix mirrors
+  /// so the mesh stays outward facing.
+  ///
+  /// matrix: The transformation.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn transform(&mut self, matrix: &Mt4) -> &mut Self {
+    self.triangles.transform(matrix);
+    self
+  }
+
+  /// Scale a mesh about the origin.
+  ///
+  /// factors: The scale along each axis, negative values mirror.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn scale(&mut self, factors: Pt3) -> &mut Self {
+    self.triangles.scale(factors);
+    self
+  }
+
+  /// Scale a mesh by the same amount along every axis about the origin.
+  ///
+  /// factor: The scale.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn scale_uniform(&mut self, factor: f64) -> &mut Self {
+    self.scale(Pt3::new(factor, factor, factor))
+  }
+
+  /// Mirror a mesh across a plane.
+  ///
+  /// normal: The normal of the plane.
+  ///
+  /// point: A point on the plane.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn mirror(&mut self, normal: Pt3, point: Pt3) -> &mut Self {
+    self.triangles.mirror(normal, point);
+    self
+  }
+
+  /// Rotate a mesh around an arbitrary axis, counter clockwise when looking down the
+  /// axis towards the pivot.
+  ///
+  /// axis: The direction of the axis.
+  ///
+  /// pivot: A point on the axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: A mutable reference to the mesh.
+  pub fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) -> &mut Self {
+    self.triangles.rotate_about(axis, pivot, degrees);
+    self
+  }
+
   /// Creates a cube primitive.
   ///
   /// x: The X dimension of the cube.
@@ -302,7 +430,7 @@ impl Mesh {
   /// return: The mesh.
   pub fn cylinder(r1: f64, r2: f64, height: f64, segments: usize, center: bool) -> Self {
     let mut result = Self::revolve(
-      &vec![
+      &[
         Pt2::new(0.0, -height / 2.0),
         Pt2::new(r1, -height / 2.0),
         Pt2::new(r2, height / 2.0),
@@ -555,13 +683,13 @@ impl Mesh {
     let mut vertices = Vec::new();
     vertices.push(profile[0].to_xz());
     vertices.push(profile[profile.len() - 1].to_xz());
-    for i in 1..(profile.len() - 1) {
-      vertices.push(profile[i].to_xz());
+    for p in &profile[1..profile.len() - 1] {
+      vertices.push(p.to_xz());
     }
     let mut indices = Vec::new();
     for segment in 1..segments {
-      for i in 1..(profile.len() - 1) {
-        let v = &profile[i].to_xz();
+      for p in &profile[1..profile.len() - 1] {
+        let v = &p.to_xz();
         vertices.push(Pt3::new(
           v.x * dcos(segment as f64 * 360.0 / segments as f64),
           v.x * dsin(segment as f64 * 360.0 / segments as f64),
@@ -603,8 +731,8 @@ impl Mesh {
   /// segments: The number of segments in a circle.
   ///
   /// return: The mesh.
-  pub fn rotate_extrude(profile: &Vec<Pt2>, degrees: f64, segments: usize) -> Self {
-    assert!(degrees >= 0.0 && degrees <= 360.0);
+  pub fn rotate_extrude(profile: &[Pt2], degrees: f64, segments: usize) -> Self {
+    assert!((0.0..=360.0).contains(&degrees));
     assert!(segments >= 3);
     let points3d: Vec<Pt3> = profile.iter().map(|p| Pt3::new(p.x, 0.0, p.y)).collect();
     let points3d_len = points3d.len();
@@ -635,8 +763,8 @@ impl Mesh {
       let mut pts: Vec<Pt3> = points3d.into_iter().rev().collect();
       let s = dsin(a * segments as f64);
       let c = dcos(a * segments as f64);
-      for i in 0..pts.len() {
-        pts[i] = Pt3::new(pts[i].x * c, pts[i].x * s, pts[i].z);
+      for pt in pts.iter_mut() {
+        *pt = Pt3::new(pt.x * c, pt.x * s, pt.z);
       }
       let nml = Pt3::new(0.0, -1.0, 0.0).rotated_z(degrees + 180.0);
       let mut indies = triangulate3d(&pts, nml);
@@ -682,13 +810,13 @@ impl Mesh {
   ///
   /// return: The mesh.
   pub fn rotate_twist_extrude(
-    profile: &Vec<Pt2>,
+    profile: &[Pt2],
     extrude_degrees: f64,
     twist_degrees: f64,
     roatation_center: Pt2,
     segments: usize,
   ) -> Self {
-    assert!(extrude_degrees >= 0.0 && extrude_degrees <= 360.0);
+    assert!((0.0..=360.0).contains(&extrude_degrees));
     assert!(segments >= 3);
     let points3d: Vec<Pt3> = profile.iter().map(|p| Pt3::new(p.x, 0.0, p.y)).collect();
     let points3d_len = points3d.len();
@@ -702,11 +830,11 @@ impl Mesh {
     for segment in 1..segments {
       let s = dsin(a * segment as f64);
       let c = dcos(a * segment as f64);
-      for p in 0..points3d.len() {
-        let mut pt = points3d[p];
-        pt = pt - Pt3::new(roatation_center.x, 0.0, roatation_center.y);
+      for (p, point) in points3d.iter().enumerate() {
+        let mut pt = *point;
+        pt -= Pt3::new(roatation_center.x, 0.0, roatation_center.y);
         pt.rotate_y(ta * segment as f64);
-        pt = pt + Pt3::new(roatation_center.x, 0.0, roatation_center.y);
+        pt += Pt3::new(roatation_center.x, 0.0, roatation_center.y);
         vertices.push(Pt3::new(pt.x * c, pt.x * s, pt.z));
         let p3 = segment * points3d_len + p;
         let p1 = segment * points3d_len + ((p + 1) % points3d_len);
@@ -721,11 +849,11 @@ impl Mesh {
       let s = dsin(a * segments as f64);
       let c = dcos(a * segments as f64);
 
-      for i in 0..pts.len() {
-        pts[i] -= Pt3::new(roatation_center.x, 0.0, roatation_center.y);
-        pts[i].rotate_y(ta * segments as f64);
-        pts[i] += Pt3::new(roatation_center.x, 0.0, roatation_center.y);
-        pts[i] = Pt3::new(pts[i].x * c, pts[i].x * s, pts[i].z);
+      for pt in pts.iter_mut() {
+        *pt -= Pt3::new(roatation_center.x, 0.0, roatation_center.y);
+        pt.rotate_y(ta * segments as f64);
+        *pt += Pt3::new(roatation_center.x, 0.0, roatation_center.y);
+        *pt = Pt3::new(pt.x * c, pt.x * s, pt.z);
       }
       let nml = Pt3::new(0.0, -1.0, 0.0).rotated_z(extrude_degrees + 180.0);
       let mut indies = triangulate3d(&pts, nml);
@@ -765,7 +893,7 @@ impl Mesh {
   /// path: The 3D path the profile is swept along.
   ///
   /// return: The resulting mesh.
-  pub fn sweep(profile: &Vec<Pt2>, path: &Vec<Pt3>, twist_degrees: f64) -> Self {
+  pub fn sweep(profile: &[Pt2], path: &[Pt3], twist_degrees: f64) -> Self {
     let profile: Vec<Pt3> = profile.iter().map(|p| p.as_pt3(0.0)).collect();
     let profile_len = profile.len();
     let mut vertices: Vec<Pt3> = Vec::new();
@@ -781,8 +909,8 @@ impl Mesh {
 
     for path_i in 1..path.len() - 1 {
       let m = Mt4::look_at_matrix_lh(path[path_i - 1], path[path_i + 1], Pt3::new(0.0, 0.0, 1.0));
-      for profile_i in 0..profile_len {
-        let point = profile_rev[profile_i].rotated_z(twist_angle * path_i as f64);
+      for (profile_i, point) in profile_rev.iter().enumerate() {
+        let point = point.rotated_z(twist_angle * path_i as f64);
         vertices.push((m * point.as_pt4(1.0)).as_pt3() + path[path_i % path.len()]);
         let p3 = path_i * profile_len + profile_i;
         let p1 = path_i * profile_len + ((profile_i + 1) % profile_len);
@@ -799,8 +927,8 @@ impl Mesh {
       Pt3::new(0.0, 0.0, 1.0),
     );
     let mut last_verts = Vec::with_capacity(profile_len);
-    for profile_i in 0..profile_len {
-      let point = profile[profile_i].rotated_z(twist_angle * (path.len() - 1) as f64);
+    for (profile_i, point) in profile.iter().enumerate() {
+      let point = point.rotated_z(twist_angle * (path.len() - 1) as f64);
       vertices.push((m * point.as_pt4(1.0)).as_pt3() + path[path.len() - 1]);
       last_verts.push((m * point.as_pt4(1.0)).as_pt3() + path[path.len() - 1]);
       let p3 = (path.len() - 1) * profile_len + (profile_len - 1 - profile_i);
@@ -830,7 +958,7 @@ impl Mesh {
   /// path: The 3D path the profile is swept along.
   ///
   /// return: The resulting mesh.
-  pub fn sweep_closed(profile: &Vec<Pt2>, path: &Vec<Pt3>, twists: i32) -> Self {
+  pub fn sweep_closed(profile: &[Pt2], path: &[Pt3], twists: i32) -> Self {
     assert!(path.len() >= 4);
     let profile: Vec<Pt3> = profile.iter().map(|p| p.as_pt3(0.0)).collect();
     let mut vertices: Vec<Pt3> = Vec::new();
@@ -884,161 +1012,126 @@ impl Mesh {
   /// Saves the mesh as an binary stl file.
   ///
   /// path: The path of the file relative to the working directory of the executable.
-  pub fn save_stl_bin(&self, path: &str) {
-    let mut header: Vec<u8> = vec![0; 80];
-    let mut v: Vec<u8> = Vec::new();
-    v.append(&mut header);
-    let mut n_triangles = (self.triangles.len() as u32).to_le_bytes().to_vec();
-    v.append(&mut n_triangles);
-    for triangle in &self.triangles {
-      let normal = triangle.normal();
-
-      let mut nx = (normal.x as f32).to_le_bytes().to_vec();
-      v.append(&mut nx);
-      let mut ny = (normal.y as f32).to_le_bytes().to_vec();
-      v.append(&mut ny);
-      let mut nz = (normal.z as f32).to_le_bytes().to_vec();
-      v.append(&mut nz);
-
-      let mut v1x = (triangle.a.x as f32).to_le_bytes().to_vec();
-      v.append(&mut v1x);
-      let mut v1y = (triangle.a.y as f32).to_le_bytes().to_vec();
-      v.append(&mut v1y);
-      let mut v1z = (triangle.a.z as f32).to_le_bytes().to_vec();
-      v.append(&mut v1z);
-
-      let mut v2x = (triangle.b.x as f32).to_le_bytes().to_vec();
-      v.append(&mut v2x);
-      let mut v2y = (triangle.b.y as f32).to_le_bytes().to_vec();
-      v.append(&mut v2y);
-      let mut v2z = (triangle.b.z as f32).to_le_bytes().to_vec();
-      v.append(&mut v2z);
-
-      let mut v3x = (triangle.c.x as f32).to_le_bytes().to_vec();
-      v.append(&mut v3x);
-      let mut v3y = (triangle.c.y as f32).to_le_bytes().to_vec();
-      v.append(&mut v3y);
-      let mut v3z = (triangle.c.z as f32).to_le_bytes().to_vec();
-      v.append(&mut v3z);
-      let mut space = 0u16.to_le_bytes().to_vec();
-      v.append(&mut space);
-    }
-    let mut file = std::fs::File::create(path).unwrap();
-    file.write_all(&v).unwrap();
-    file.flush().unwrap();
+  ///
+  /// return: An error if the file can't be written.
+  pub fn save_stl_bin(&self, path: impl AsRef<Path>) -> Result<()> {
+    self.write_stl_bin(File::create(path)?)
+  }
+
+  /// Writes the mesh as binary stl.
+  ///
+  /// writer: Where the bytes go.
+  ///
+  /// return: An error if writing fails.
+  pub fn write_stl_bin(&self, writer: impl Write) -> Result<()> {
+    stl::write_binary(&self.triangles, writer)
   }
 
   /// Saves the mesh as an ascii stl file.
   ///
   /// path: The path of the file relative to the working directory of the executable.
-  pub fn save_stl_ascii(&self, path: &str) {
-    let mut v: Vec<u8> = Vec::new();
-    v.append(&mut b"solid ".to_vec());
-    v.append(&mut path.as_bytes().to_vec());
-    v.append(&mut b"\n".to_vec());
-    for triangle in &self.triangles {
-      let normal = triangle.normal();
-      v.append(
-        &mut format!(
-          "facet normal {} {} {}\n",
-          normal.x as f32, normal.y as f32, normal.z as f32
-        )
-        .as_bytes()
-        .to_vec(),
-      );
-      v.append(&mut b"    outer loop\n".to_vec());
-      v.append(
-        &mut format!(
-          "        vertex {} {} {}\n",
-          triangle.a.x as f32, triangle.a.y as f32, triangle.a.z as f32
-        )
-        .as_bytes()
-        .to_vec(),
-      );
-      v.append(
-        &mut format!(
-          "        vertex {} {} {}\n",
-          triangle.b.x as f32, triangle.b.y as f32, triangle.b.z as f32
-        )
-        .as_bytes()
-        .to_vec(),
-      );
-      v.append(
-        &mut format!(
-          "        vertex {} {} {}\n",
-          triangle.c.x as f32, triangle.c.y as f32, triangle.c.z as f32
-        )
-        .as_bytes()
-        .to_vec(),
-      );
-      v.append(&mut b"    endloop\n".to_vec());
-      v.append(&mut b"endfacet\n".to_vec());
-    }
-    v.append(&mut b"endsolid ".to_vec());
-    v.append(&mut path.as_bytes().to_vec());
-    v.append(&mut b"\n".to_vec());
+  ///
+  /// return: An error if the file can't be written.
+  pub fn save_stl_ascii(&self, path: impl AsRef<Path>) -> Result<()> {
+    let name = path.as_ref().to_string_lossy().into_owned();
+    self.write_stl_ascii(File::create(path)?, &name)
+  }
 
-    let mut file = std::fs::File::create(path).unwrap();
-    file.write_all(&v).unwrap();
-    file.flush().unwrap();
+  /// Writes the mesh as ascii stl.
+  ///
+  /// writer: Where the text goes.
+  ///
+  /// name: The name of the solid.
+  ///
+  /// return: An error if writing fails.
+  pub fn write_stl_ascii(&self, writer: impl Write, name: &str) -> Result<()> {
+    stl::write_ascii(&self.triangles, writer, name)
   }
 
-  /// Load an stl file.
+  /// Load an stl file, ascii or binary.
   ///
   /// path: The path of the file relative to the working directory of the executable.
   ///
-  /// return: The mesh.
-  pub fn load_stl(path: &str) -> Self {
-    let mut file = std::fs::File::open(path).unwrap();
-    let mut data = Vec::new();
-    file.read_to_end(&mut data).unwrap();
-    assert!(data.len() > 5);
-    if data[0] == b's' && data[1] == b'o' && data[2] == b'l' && data[3] == b'i' && data[4] == b'd' {
-      Self::parse_ascii(data)
-    } else {
-      Self::parse_binary(data)
-    }
+  /// return: The mesh or an error if the file can't be read or is not stl.
+  pub fn load_stl(path: impl AsRef<Path>) -> Result<Self> {
+    Self::read_stl_seekable(File::open(path)?)
   }
 
-  /// Parse the binary stl data.
+  /// Reads ascii or binary stl. Without the length of the data binary is told from
+  /// ascii by the first bytes, use read_stl_seekable when the source can seek.
   ///
-  /// data: The bytes of the file.
+  /// reader: The source of the bytes, it is read to the end.
   ///
-  /// return: The mesh.
-  fn parse_binary(data: Vec<u8>) -> Self {
-    let ptr = &data[80] as *const u8 as *const u32;
-    let n_triangles = unsafe { *ptr };
-    let mut triangles = Vec::with_capacity(n_triangles as usize);
-    for i in 0..n_triangles as usize {
-      let ptr = &data[84 + i * 50] as *const u8 as *const f32;
-      //let normal;
-      let vert1;
-      let vert2;
-      let vert3;
-      unsafe {
-        vert1 = Pt3::new(
-          *ptr.offset(3) as f64,
-          *ptr.offset(4) as f64,
-          *ptr.offset(5) as f64,
-        );
-        vert2 = Pt3::new(
-          *ptr.offset(6) as f64,
-          *ptr.offset(7) as f64,
-          *ptr.offset(8) as f64,
-        );
-        vert3 = Pt3::new(
-          *ptr.offset(9) as f64,
-          *ptr.offset(10) as f64,
-          *ptr.offset(11) as f64,
-        );
-      }
-      triangles.push(Triangle::new(vert1, vert2, vert3));
-    }
-    Self::from_triangles(triangles)
+  /// return: The mesh or an error if reading fails or the data is not stl.
+  pub fn read_stl(reader: impl Read) -> Result<Self> {
+    Ok(Self::from_triangles(stl::read(reader, None)?))
+  }
+
+  /// Reads ascii or binary stl from a source that can seek, binary is told from ascii
+  /// by the length of the data.
+  ///
+  /// reader: The source of the bytes, it is read from where it is to the end.
+  ///
+  /// return: The mesh or an error if reading fails or the data is not stl.
+  pub fn read_stl_seekable(mut reader: impl Read + Seek) -> Result<Self> {
+    let start = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
+    Ok(Self::from_triangles(stl::read(
+      reader,
+      Some(end.saturating_sub(start)),
+    )?))
   }
 
-  fn parse_ascii(_data: Vec<u8>) -> Self {
-    panic!("Loading ascii stl files is not implemented.")
+  /// Saves the mesh as a Wavefront obj file with shared vertices.
+  ///
+  /// path: The path of the file relative to the working directory of the executable.
+  ///
+  /// return: An error if the file can't be written.
+  pub fn save_obj(&self, path: impl AsRef<Path>) -> Result<()> {
+    let name = path
+      .as_ref()
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    self.write_obj(File::create(path)?, &name)
+  }
+
+  /// Writes the mesh as Wavefront obj with shared vertices.
+  ///
+  /// writer: Where the text goes.
+  ///
+  /// name: The name of the object, left out if empty.
+  ///
+  /// return: An error if writing fails.
+  pub fn write_obj(&self, writer: impl Write, name: &str) -> Result<()> {
+    obj::write(&self.triangles, writer, name)
+  }
+
+  /// Load a Wavefront obj file. Faces with more than three vertices are triangulated,
+  /// normals, texture coordinates and materials are ignored.
+  ///
+  /// path: The path of the file relative to the working directory of the executable.
+  ///
+  /// return: A mesh for each object or group with its name, an empty name for faces
+  /// before the first one, or an error if the file can't be read or is not obj.
+  pub fn load_obj(path: impl AsRef<Path>) -> Result<Vec<(String, Self)>> {
+    Self::read_obj(File::open(path)?)
+  }
+
+  /// Reads Wavefront obj, see [`Mesh::load_obj`].
+  ///
+  /// reader: The source of the text.
+  ///
+  /// return: A mesh for each object or group with its name or an error if reading fails
+  /// or the text is not obj.
+  pub fn read_obj(reader: impl Read) -> Result<Vec<(String, Self)>> {
+    Ok(
+      obj::parse(BufReader::new(reader))?
+        .into_iter()
+        .map(|(name, triangles)| (name, Self::from_triangles(triangles)))
+        .collect(),
+    )
   }
 }
 
@@ -1054,7 +1147,10 @@ impl std::ops::Add for Mesh {
 
 impl std::ops::AddAssign for Mesh {
   fn add_assign(&mut self, rhs: Self) {
-    *self = self.clone() + rhs;
+    let lhs = Mesh {
+      triangles: std::mem::take(&mut self.triangles),
+    };
+    *self = lhs + rhs;
   }
 }
 
@@ -1070,7 +1166,10 @@ impl std::ops::Sub for Mesh {
 
 impl std::ops::SubAssign for Mesh {
   fn sub_assign(&mut self, rhs: Self) {
-    *self = self.clone() - rhs;
+    let lhs = Mesh {
+      triangles: std::mem::take(&mut self.triangles),
+    };
+    *self = lhs - rhs;
   }
 }
 
@@ -1086,6 +1185,9 @@ impl std::ops::Mul for Mesh {
 
 impl std::ops::MulAssign for Mesh {
   fn mul_assign(&mut self, rhs: Self) {
-    *self = self.clone() * rhs
+    let lhs = Mesh {
+      triangles: std::mem::take(&mut self.triangles),
+    };
+    *self = lhs * rhs
   }
 }
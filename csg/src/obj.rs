@@ -0,0 +1,269 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reading and writing of Wavefront obj files.
+
+use {
+  crate::{repair::triangulate, Error, IndexedMesh, Pt3, Result, Triangle},
+  std::io::{BufRead, BufWriter, Write},
+};
+
+/// Parse obj text. Faces can use any of the v, v/vt, v/vt/vn and v//vn forms with
+/// positive or negative indices, faces with more than three vertices are triangulated,
+/// a concave face that can't be triangulated is an error, and statements that don't
+/// describe the shape, like materials and smoothing groups, are skipped. Each object or
+/// group becomes a separate list of triangles, ones without faces are left out.
+///
+/// reader: The source of the text.
+///
+/// return: The named objects and their triangles or an error if reading fails or the
+/// text is not obj.
+pub(crate) fn parse(mut reader: impl BufRead) -> Result<Vec<(String, Vec<Triangle>)>> {
+  let mut objects = Vec::new();
+  let mut name = String::new();
+  let mut triangles = Vec::new();
+  let mut vertices: Vec<Pt3> = Vec::new();
+  let mut normals = 0;
+  let mut texture_coordinates = 0;
+  let mut bytes = Vec::new();
+  let mut line_number = 0;
+  loop {
+    // a backslash at the end of a line joins it with the next one
+    let mut line = String::new();
+    let start = line_number + 1;
+    loop {
+      bytes.clear();
+      if reader.read_until(b'\n', &mut bytes)? == 0 {
+        break;
+      }
+      line_number += 1;
+      let text = String::from_utf8_lossy(&bytes);
+      let text = text.trim_end();
+      match text.strip_suffix('\\') {
+        Some(text) => {
+          line.push_str(text);
+          line.push(' ');
+        }
+        None => {
+          line.push_str(text);
+          break;
+        }
+      }
+    }
+    if line_number < start {
+      break;
+    }
+    let error = |message: String| Error::Syntax {
+      line: start,
+      message,
+    };
+    let line = line.split('#').next().unwrap_or("");
+    let mut tokens = line.split_ascii_whitespace();
+    match tokens.next() {
+      Some("v") => {
+        let mut number = || {
+          tokens
+            .next()
+            .and_then(|token| token.parse::<f64>().ok())
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| error("expected a number".to_string()))
+        };
+        vertices.push(Pt3::new(number()?, number()?, number()?));
+      }
+      Some("vn") => normals += 1,
+      Some("vt") => texture_coordinates += 1,
+      Some("f") => {
+        let mut face = Vec::new();
+        for token in tokens {
+          let mut parts = token.split('/');
+          let vertex = parts.next().unwrap_or("");
+          face.push(resolve(vertex, vertices.len()).map_err(&error)?);
+          if let Some(texture_coordinate) = parts.next().filter(|part| !part.is_empty()) {
+            resolve(texture_coordinate, texture_coordinates).map_err(&error)?;
+          }
+          if let Some(normal) = parts.next().filter(|part| !part.is_empty()) {
+            resolve(normal, normals).map_err(&error)?;
+          }
+        }
+        if face.len() < 3 {
+          return Err(error("a face needs at least three vertices".to_string()));
+        }
+        let points: Vec<Pt3> = face.iter().map(|&v| vertices[v]).collect();
+        let corners = triangulate_face(&points)
+          .ok_or_else(|| error("the face can't be triangulated".to_string()))?;
+        for t in corners.chunks(3) {
+          triangles.push(Triangle::new(points[t[0]], points[t[1]], points[t[2]]));
+        }
+      }
+      Some("o") | Some("g") => {
+        if !triangles.is_empty() {
+          objects.push((std::mem::take(&mut name), std::mem::take(&mut triangles)));
+        }
+        name = tokens.collect::<Vec<&str>>().join(" ");
+      }
+      _ => (),
+    }
+  }
+  if !triangles.is_empty() {
+    objects.push((name, triangles));
+  }
+  Ok(objects)
+}
+
+/// Triangulate a face. A face the triangulator can't handle is split into a fan if it
+/// is convex, a fan of a concave face would cover more than the face does.
+///
+/// points: The corners of the face.
+///
+/// return: Indices into points, three for each triangle, or None if the face is
+/// concave and can't be triangulated, like one whose outline crosses itself.
+fn triangulate_face(points: &[Pt3]) -> Option<Vec<usize>> {
+  let n = points.len();
+  if n == 3 {
+    return Some(vec![0, 1, 2]);
+  }
+  let mut normal = Pt3::new(0.0, 0.0, 0.0);
+  for i in 0..n {
+    normal += points[i].cross(points[(i + 1) % n]);
+  }
+  if let Some(indices) = triangulate(points, normal) {
+    return Some(indices);
+  }
+  let convex = (0..n).all(|i| {
+    let (a, b, c) = (points[i], points[(i + 1) % n], points[(i + 2) % n]);
+    (b - a).cross(c - b).dot(normal) >= 0.0
+  });
+  convex.then(|| (2..n).flat_map(|i| [0, i - 1, i]).collect())
+}
+
+/// Turn an obj index into an index into a list, positive indices count from 1 at the
+/// start of the list and negative ones from -1 at the end.
+///
+/// token: The index as text.
+///
+/// len: The length of the list so far.
+///
+/// return: The index or a message saying why it is not valid.
+fn resolve(token: &str, len: usize) -> std::result::Result<usize, String> {
+  let index: i64 = token
+    .parse()
+    .map_err(|_| format!("\"{}\" is not an index", token))?;
+  let resolved = if index > 0 {
+    usize::try_from(index - 1).ok()
+  } else {
+    usize::try_from(len as i64 + index).ok()
+  };
+  match resolved {
+    Some(resolved) if index != 0 && resolved < len => Ok(resolved),
+    _ => Err(format!("index {} is out of range", index)),
+  }
+}
+
+/// Write triangles as an obj object. Points that are exactly equal are written once
+/// and shared by the faces that use them, the numbers are written with enough digits to
+/// read back exactly.
+///
+/// triangles: The triangles to write.
+///
+/// writer: Where the text goes.
+///
+/// name: The name of the object, no object statement is written if it is empty.
+///
+/// return: An error if writing fails.
+pub(crate) fn write(triangles: &[Triangle], writer: impl Write, name: &str) -> Result<()> {
+  let mesh = IndexedMesh::from_triangles(triangles);
+  let mut writer = BufWriter::new(writer);
+  if !name.is_empty() {
+    writeln!(writer, "o {}", name)?;
+  }
+  for v in &mesh.vertices {
+    writeln!(writer, "v {} {} {}", v.x, v.y, v.z)?;
+  }
+  for [a, b, c] in &mesh.triangles {
+    writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The triangles of the one object in some obj text.
+  fn faces(text: &str) -> Result<Vec<Triangle>> {
+    let mut objects = parse(text.as_bytes())?;
+    assert_eq!(objects.len(), 1);
+    Ok(objects.remove(0).1)
+  }
+
+  /// The total area of some triangles, overlaps counted twice.
+  fn area(triangles: &[Triangle]) -> f64 {
+    triangles
+      .iter()
+      .map(|t| (t.b - t.a).cross(t.c - t.a).len() / 2.0)
+      .sum()
+  }
+
+  #[test]
+  fn convex_face() {
+    let triangles = faces("v 0 0 0\nv 2 0 0\nv 2 1 0\nv 0 1 0\nf 1 2 3 4\n").unwrap();
+    assert_eq!(triangles.len(), 2);
+    assert_eq!(area(&triangles), 2.0);
+  }
+
+  #[test]
+  fn concave_face_with_a_straight_corner() {
+    // an L of three unit squares with a vertex in the middle of the bottom edge,
+    // starting at a corner that can't see the whole face
+    let text = "
+      v 1 2 0
+      v 0 2 0
+      v 0 0 0
+      v 1 0 0
+      v 2 0 0
+      v 2 1 0
+      v 1 1 0
+      f 1 2 3 4 5 6 7
+    ";
+    let triangles = faces(text).unwrap();
+    assert_eq!(triangles.len(), 5);
+    assert_eq!(area(&triangles), 3.0);
+    assert!(triangles.iter().all(|t| t.normal().z > 0.0));
+  }
+
+  #[test]
+  fn face_that_crosses_itself() {
+    let text = "v 0 0 0\nv 1 1 0\nv 1 0 0\nv 0 1 0\nv 0.5 2 0\nf 1 2 3 4 5\n";
+    assert!(matches!(faces(text), Err(Error::Syntax { line: 6, .. })));
+  }
+
+  #[test]
+  fn round_trip() {
+    let cube = crate::Mesh::cube(10.0, 10.0, 10.0, false);
+    let mut text = Vec::new();
+    write(&cube.triangles, &mut text, "cube").unwrap();
+    let objects = parse(&text[..]).unwrap();
+    assert_eq!(objects[0].0, "cube");
+    assert_eq!(objects[0].1.len(), 12);
+  }
+}
@@ -0,0 +1,317 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Repair of broken meshes, like STL files with flipped facets and small gaps.
+//!
+//! The triangles are indexed by their exact points, duplicates are removed, the
+//! winding is made consistent across each connected piece, small holes are filled
+//! and every piece is turned so it encloses a positive volume.
+
+use {
+  crate::{
+    ear_clip::{cross, project, try_triangulate3d},
+    IndexedMesh, Pt2, Pt3, Triangle,
+  },
+  std::collections::{HashMap, HashSet, VecDeque},
+};
+
+/// The sine of the smallest turn a hole outline can make at a vertex and still be
+/// a corner instead of a straight vertex.
+const SINE_EPSILON: f64 = 1.0e-10;
+
+/// Repair a list of triangles.
+///
+/// triangles: The triangles to repair.
+///
+/// max_hole_edges: Holes with more edges than this are left open.
+///
+/// return: The repaired triangles.
+pub(crate) fn repair(triangles: &[Triangle], max_hole_edges: usize) -> Vec<Triangle> {
+  let mut mesh = IndexedMesh::from_triangles(triangles);
+  let mut seen = HashSet::new();
+  mesh.triangles.retain(|&[a, b, c]| {
+    let mut key = [a, b, c];
+    key.sort();
+    a != b && b != c && c != a && seen.insert(key)
+  });
+
+  unify_winding(&mut mesh.triangles);
+  fill_holes(&mut mesh, max_hole_edges);
+  let pieces = unify_winding(&mut mesh.triangles);
+
+  // a closed piece with the normals pointing in has a negative volume
+  let mut volumes = vec![0.0; mesh.triangles.len()];
+  for (t, &[a, b, c]) in mesh.triangles.iter().enumerate() {
+    let [a, b, c] = [a, b, c].map(|i| mesh.vertices[i]);
+    volumes[pieces[t]] += a.dot(b.cross(c));
+  }
+  for (t, triangle) in mesh.triangles.iter_mut().enumerate() {
+    if volumes[pieces[t]] < 0.0 {
+      triangle.swap(1, 2);
+    }
+  }
+  mesh.to_mesh().triangles
+}
+
+/// Flip triangles so the two triangles on every edge they share go opposite ways.
+/// Edges with more than two triangles don't connect anything.
+///
+/// return: For each triangle the first triangle of the piece it is in.
+fn unify_winding(triangles: &mut [[usize; 3]]) -> Vec<usize> {
+  let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+  for (t, triangle) in triangles.iter().enumerate() {
+    for k in 0..3 {
+      let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+      edges.entry((a.min(b), a.max(b))).or_default().push(t);
+    }
+  }
+  // whether a triangle goes from the lower to the higher vertex of an edge
+  let forward = |triangle: &[usize; 3], (low, high): (usize, usize)| {
+    let k = triangle.iter().position(|&v| v == low).unwrap();
+    triangle[(k + 1) % 3] == high
+  };
+
+  let mut pieces = vec![usize::MAX; triangles.len()];
+  let mut queue = VecDeque::new();
+  for start in 0..triangles.len() {
+    if pieces[start] != usize::MAX {
+      continue;
+    }
+    pieces[start] = start;
+    queue.push_back(start);
+    while let Some(t) = queue.pop_front() {
+      for k in 0..3 {
+        let (a, b) = (triangles[t][k], triangles[t][(k + 1) % 3]);
+        let key = (a.min(b), a.max(b));
+        let uses = &edges[&key];
+        if uses.len() != 2 {
+          continue;
+        }
+        let u = if uses[0] == t { uses[1] } else { uses[0] };
+        if pieces[u] != usize::MAX {
+          continue;
+        }
+        if forward(&triangles[u], key) == forward(&triangles[t], key) {
+          triangles[u].swap(1, 2);
+        }
+        pieces[u] = start;
+        queue.push_back(u);
+      }
+    }
+  }
+  pieces
+}
+
+/// Fill the holes with up to max_edges edges. A hole that touches another hole at a
+/// vertex or can't be triangulated cleanly is left open.
+fn fill_holes(mesh: &mut IndexedMesh, max_edges: usize) {
+  let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+  for &[a, b, c] in &mesh.triangles {
+    for (p, q) in [(a, b), (b, c), (c, a)] {
+      *counts.entry((p.min(q), p.max(q))).or_default() += 1;
+    }
+  }
+  // the boundary edges in the direction of their triangles, at most one per vertex
+  let mut next: HashMap<usize, usize> = HashMap::new();
+  let mut pinched = HashSet::new();
+  let mut starts = Vec::new();
+  for &[a, b, c] in &mesh.triangles {
+    for (p, q) in [(a, b), (b, c), (c, a)] {
+      if counts[&(p.min(q), p.max(q))] == 1 {
+        if next.insert(p, q).is_some() {
+          pinched.insert(p);
+        }
+        starts.push(p);
+      }
+    }
+  }
+
+  let mut visited = HashSet::new();
+  let mut patches = Vec::new();
+  for start in starts {
+    if visited.contains(&start) {
+      continue;
+    }
+    let mut hole = Vec::new();
+    let mut v = start;
+    let mut closed = false;
+    while visited.insert(v) {
+      hole.push(v);
+      match next.get(&v) {
+        Some(&n) => v = n,
+        None => break,
+      }
+      closed = v == start;
+    }
+    if !closed || hole.len() > max_edges || hole.iter().any(|v| pinched.contains(v)) {
+      continue;
+    }
+    // the patch goes around the hole the other way from the triangles next to it
+    hole.reverse();
+    if let Some(mut patch) = patch(&hole, &mesh.vertices) {
+      patches.append(&mut patch);
+    }
+  }
+  mesh.triangles.append(&mut patches);
+}
+
+/// Triangulate a polygon, like the outline of a hole. Straight corners can't be the tip
+/// of a triangle, so the polygon of the other corners is triangulated and each straight
+/// corner is put back by fanning the triangle on its edge out to it.
+///
+/// return: The triangles or None if the outline isn't a simple polygon when seen
+/// along its normal.
+fn patch(hole: &[usize], vertices: &[Pt3]) -> Option<Vec<[usize; 3]>> {
+  if hole.len() == 3 {
+    return Some(vec![[hole[0], hole[1], hole[2]]]);
+  }
+  let points: Vec<Pt3> = hole.iter().map(|&v| vertices[v]).collect();
+  let n = points.len();
+  let mut normal = Pt3::new(0.0, 0.0, 0.0);
+  for i in 0..n {
+    normal += points[i].cross(points[(i + 1) % n]);
+  }
+  let outline: Vec<Pt2> = points.iter().map(|&p| project(p, normal)).collect();
+
+  // the positions in the hole of the corners that aren't straight, an outline that
+  // turns back on itself is not simple
+  let mut corners = Vec::with_capacity(n);
+  for i in 0..n {
+    let (a, b, c) = (outline[(i + n - 1) % n], outline[i], outline[(i + 1) % n]);
+    if cross(a, b, c).abs() > SINE_EPSILON * (b - a).len() * (c - b).len() {
+      corners.push(i);
+    } else if (b - a).dot(c - b) <= 0.0 {
+      return None;
+    }
+  }
+  let m = corners.len();
+  if m < 3 {
+    return None;
+  }
+  let mut area = 0.0;
+  for i in 0..m {
+    let (a, b) = (outline[corners[i]], outline[corners[(i + 1) % m]]);
+    area += cross(Pt2::new(0.0, 0.0), a, b);
+    for j in (i + 2)..m {
+      let (c, d) = (outline[corners[j]], outline[corners[(j + 1) % m]]);
+      if (j + 1) % m != i && segments_intersect(a, b, c, d) {
+        return None;
+      }
+    }
+  }
+  if area <= 0.0 {
+    return None;
+  }
+
+  let kept: Vec<Pt3> = corners.iter().map(|&i| points[i]).collect();
+  let indices = if m == 3 {
+    vec![0, 1, 2]
+  } else {
+    triangulate(&kept, normal)?
+  };
+  let mut triangles: Vec<[usize; 3]> = indices
+    .chunks(3)
+    .map(|t| [corners[t[0]], corners[t[1]], corners[t[2]]])
+    .collect();
+  for i in 0..m {
+    let (p, q) = (corners[i], corners[(i + 1) % m]);
+    if (p + 1) % n == q {
+      continue;
+    }
+    // the triangle on the edge from p to q and its third corner
+    let t = triangles
+      .iter()
+      .position(|t| (0..3).any(|k| t[k] == p && t[(k + 1) % 3] == q))?;
+    let triangle = triangles.swap_remove(t);
+    let r = triangle[(triangle.iter().position(|&v| v == p)? + 2) % 3];
+    let mut a = p;
+    while a != q {
+      let b = (a + 1) % n;
+      triangles.push([a, b, r]);
+      a = b;
+    }
+  }
+  Some(triangles.into_iter().map(|t| t.map(|i| hole[i])).collect())
+}
+
+/// Triangulate a polygon whose outline doesn't cross itself.
+///
+/// points: The corners of the polygon, counter clockwise around normal.
+///
+/// normal: The normal of the polygon.
+///
+/// return: Indices into points, three for each triangle, or None if the polygon can't
+/// be triangulated without overlapping triangles.
+pub(crate) fn triangulate(points: &[Pt3], normal: Pt3) -> Option<Vec<usize>> {
+  // The triangulator's tolerances are absolute, so it gets the outline scaled to a
+  // unit size or the triangles of a small polygon would all look flat.
+  let mut min = points[0];
+  let mut max = points[0];
+  for p in points {
+    min = Pt3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+    max = Pt3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+  }
+  let size = (max - min).len();
+  let scaled: Vec<Pt3> = points.iter().map(|&p| (p - min) / size).collect();
+  let indices = try_triangulate3d(&scaled, normal)?;
+
+  // triangles that overlap cover more than the outline does
+  let outline: Vec<Pt2> = scaled.iter().map(|&p| project(p, normal)).collect();
+  let n = outline.len();
+  let area: f64 = (0..n)
+    .map(|i| cross(Pt2::new(0.0, 0.0), outline[i], outline[(i + 1) % n]))
+    .sum();
+  let covered: f64 = indices
+    .chunks(3)
+    .map(|t| cross(outline[t[0]], outline[t[1]], outline[t[2]]).abs())
+    .sum();
+  if area <= 0.0 || covered - area > 1.0e-6 * area {
+    return None;
+  }
+  Some(indices)
+}
+
+/// Whether the segments ab and cd touch or cross.
+fn segments_intersect(a: Pt2, b: Pt2, c: Pt2, d: Pt2) -> bool {
+  let d1 = cross(a, b, c);
+  let d2 = cross(a, b, d);
+  let d3 = cross(c, d, a);
+  let d4 = cross(c, d, b);
+  if ((d1 > 0.0) != (d2 > 0.0) || d1 == 0.0 || d2 == 0.0)
+    && ((d3 > 0.0) != (d4 > 0.0) || d3 == 0.0 || d4 == 0.0)
+  {
+    // collinear segments only touch if they overlap
+    if d1 == 0.0 && d2 == 0.0 {
+      let (lo, hi) = (
+        a.x.min(b.x).max(c.x.min(d.x)),
+        a.x.max(b.x).min(c.x.max(d.x)),
+      );
+      let (low, high) = (
+        a.y.min(b.y).max(c.y.min(d.y)),
+        a.y.max(b.y).min(c.y.max(d.y)),
+      );
+      return lo <= hi && low <= high;
+    }
+    return true;
+  }
+  false
+}
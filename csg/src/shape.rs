@@ -0,0 +1,418 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A shape is a lazy description of a model.
+//!
+//! The operators on Mesh do a boolean operation right away and convert the meshes to
+//! CSG and back every time. A shape only records the operations, like an OpenSCAD
+//! script, and evaluate does all of them at the end. Transforms are multiplied
+//! together and applied once to the meshes at the leaves, unions and differences
+//! are done with the balanced n-ary operations and intersections start with the
+//! smallest operands.
+
+use crate::{
+  cache::{Cache, StableHasher},
+  CsgOptions, Mesh, Mt4, Pt3, Triangle, VecTriangle, CSG,
+};
+
+#[derive(Clone)]
+pub enum Shape {
+  /// A mesh, the leaves of the tree.
+  Mesh(Mesh),
+  /// A shape moved by a matrix.
+  Transform(Mt4, Box<Shape>),
+  /// Everything that is inside any of the shapes.
+  Union(Vec<Shape>),
+  /// The first shape with the rest of the shapes removed from it.
+  Difference(Box<Shape>, Vec<Shape>),
+  /// Everything that is inside all of the shapes.
+  Intersection(Vec<Shape>),
+}
+
+impl Shape {
+  /// Creates a shape from a mesh.
+  ///
+  /// mesh: The mesh.
+  ///
+  /// return: The shape.
+  pub fn mesh(mesh: Mesh) -> Self {
+    Shape::Mesh(mesh)
+  }
+
+  /// Move a shape by a matrix.
+  ///
+  /// matrix: The transformation.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn transform(&mut self, matrix: Mt4) -> &mut Self {
+    *self = match self.take() {
+      Shape::Transform(inner, shape) => Shape::Transform(matrix * inner, shape),
+      shape => Shape::Transform(matrix, Box::new(shape)),
+    };
+    self
+  }
+
+  /// Translate a shape by the given vector.
+  ///
+  /// v: The translation vector.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn translate(&mut self, v: Pt3) -> &mut Self {
+    self.transform(Mt4::translate_matrix(v.x, v.y, v.z))
+  }
+
+  /// Rotate a shape around the X axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn rotate_x(&mut self, degrees: f64) -> &mut Self {
+    self.transform(Mt4::rot_x_matrix(degrees))
+  }
+
+  /// Rotate a shape around the Y axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn rotate_y(&mut self, degrees: f64) -> &mut Self {
+    // Mesh::rotate_y turns the other way from Mt4::rot_y_matrix
+    self.transform(Mt4::rot_y_matrix(-degrees))
+  }
+
+  /// Rotate a shape around the Z axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn rotate_z(&mut self, degrees: f64) -> &mut Self {
+    self.transform(Mt4::rot_z_matrix(degrees))
+  }
+
+  /// Scale a shape about the origin.
+  ///
+  /// factors: The scale along each axis, negative values mirror.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn scale(&mut self, factors: Pt3) -> &mut Self {
+    self.transform(Mt4::scale_matrix(factors.x, factors.y, factors.z))
+  }
+
+  /// Scale a shape by the same amount along every axis about the origin.
+  ///
+  /// factor: The scale.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn scale_uniform(&mut self, factor: f64) -> &mut Self {
+    self.transform(Mt4::scale_matrix(factor, factor, factor))
+  }
+
+  /// Mirror a shape across a plane.
+  ///
+  /// normal: The normal of the plane.
+  ///
+  /// point: A point on the plane.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn mirror(&mut self, normal: Pt3, point: Pt3) -> &mut Self {
+    self.transform(Mt4::mirror_matrix(normal, point))
+  }
+
+  /// Rotate a shape around an arbitrary axis, counter clockwise when looking down the
+  /// axis towards the pivot.
+  ///
+  /// axis: The direction of the axis.
+  ///
+  /// pivot: A point on the axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: A mutable reference to the shape.
+  pub fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) -> &mut Self {
+    self.transform(Mt4::rot_axis_matrix(axis, pivot, degrees))
+  }
+
+  /// Do the operations described by the shape.
+  ///
+  /// return: The mesh of the shape.
+  pub fn evaluate(&self) -> Mesh {
+    self.evaluate_with(&CsgOptions::default())
+  }
+
+  /// Do the operations described by the shape with the given tolerances.
+  ///
+  /// options: The tolerances of the boolean operations.
+  ///
+  /// return: The mesh of the shape.
+  pub fn evaluate_with(&self, options: &CsgOptions) -> Mesh {
+    options.assert_valid();
+    Mesh::from_csg(self.to_csg(Mt4::identity(), options, None))
+  }
+
+  /// Do the operations described by the shape, loading the results of booleans that
+  /// were done before from a cache and storing the new ones.
+  ///
+  /// options: The tolerances of the boolean operations.
+  ///
+  /// cache: Where the results of earlier runs are kept.
+  ///
+  /// return: The mesh of the shape.
+  pub fn evaluate_cached(&self, options: &CsgOptions, cache: &Cache) -> Mesh {
+    options.assert_valid();
+    let keys = self.keys(Mt4::identity(), options);
+    Mesh::from_csg(self.to_csg(Mt4::identity(), options, Some((cache, &keys))))
+  }
+
+  /// Evaluate the shape moved by a matrix.
+  ///
+  /// cache: The cache and the keys of this shape when the results are cached.
+  fn to_csg(&self, matrix: Mt4, options: &CsgOptions, cache: Option<(&Cache, &Keys)>) -> CSG {
+    let store = match (self, cache) {
+      (Shape::Union(_) | Shape::Difference(..) | Shape::Intersection(_), Some((cache, keys))) => {
+        if let Some(csg) = cache.load(keys.key) {
+          return csg;
+        }
+        Some((cache, keys.key))
+      }
+      _ => None,
+    };
+    let csg = self.evaluate_node(matrix, options, cache);
+    if let Some((cache, key)) = store {
+      cache.store(key, &csg);
+    }
+    csg
+  }
+
+  fn evaluate_node(
+    &self,
+    matrix: Mt4,
+    options: &CsgOptions,
+    cache: Option<(&Cache, &Keys)>,
+  ) -> CSG {
+    // the children of the keys are in the same order as the children of the shape
+    let child = |i: usize| cache.map(|(cache, keys)| (cache, &keys.children[i]));
+    match self {
+      Shape::Mesh(mesh) => CSG::from_triangles(transformed(&mesh.triangles, matrix)),
+      Shape::Transform(inner, shape) => shape.to_csg(matrix * *inner, options, child(0)),
+      Shape::Union(shapes) => CSG::union_all_with(
+        shapes
+          .iter()
+          .enumerate()
+          .map(|(i, s)| s.to_csg(matrix, options, child(i))),
+        options,
+      ),
+      Shape::Difference(base, tools) => {
+        let base = base.to_csg(matrix, options, child(0));
+        if base.polygons.is_empty() {
+          return base;
+        }
+        CSG::subtract_all_with(
+          base,
+          tools
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.to_csg(matrix, options, child(i + 1))),
+          options,
+        )
+      }
+      Shape::Intersection(shapes) => {
+        // starting with the smallest operands keeps the intermediate results small
+        let mut operands: Vec<CSG> = shapes
+          .iter()
+          .enumerate()
+          .map(|(i, s)| s.to_csg(matrix, options, child(i)))
+          .collect();
+        operands.sort_by_key(|csg| csg.polygons.len());
+        let mut operands = operands.into_iter();
+        let mut result = operands.next().unwrap_or_default();
+        for operand in operands {
+          if result.polygons.is_empty() {
+            break;
+          }
+          result = result.intersect_with(operand, options);
+        }
+        result
+      }
+    }
+  }
+
+  /// Hash everything that goes into the result of the shape and of each shape in
+  /// it. The keys are worked out bottom up so each mesh is only hashed once.
+  fn keys(&self, matrix: Mt4, options: &CsgOptions) -> Keys {
+    let mut hasher = StableHasher::new();
+    let children = match self {
+      Shape::Mesh(mesh) => {
+        hasher.u64(0);
+        for column in [matrix.x, matrix.y, matrix.z, matrix.w] {
+          for v in [column.x, column.y, column.z, column.w] {
+            hasher.f64(v);
+          }
+        }
+        hasher.u64(mesh.triangles.len() as u64);
+        for t in &mesh.triangles {
+          hasher.pt3(t.a);
+          hasher.pt3(t.b);
+          hasher.pt3(t.c);
+        }
+        Vec::new()
+      }
+      Shape::Transform(inner, shape) => {
+        // a transform has the result of the shape it moves
+        let child = shape.keys(matrix * *inner, options);
+        return Keys {
+          key: child.key,
+          children: vec![child],
+        };
+      }
+      Shape::Union(shapes) | Shape::Intersection(shapes) => {
+        hasher.u64(if matches!(self, Shape::Union(_)) {
+          1
+        } else {
+          3
+        });
+        hasher.options(options);
+        hasher.u64(shapes.len() as u64);
+        let children: Vec<Keys> = shapes.iter().map(|s| s.keys(matrix, options)).collect();
+        for child in &children {
+          hasher.u128(child.key);
+        }
+        children
+      }
+      Shape::Difference(base, tools) => {
+        hasher.u64(2);
+        hasher.options(options);
+        let mut children = vec![base.keys(matrix, options)];
+        hasher.u128(children[0].key);
+        hasher.u64(tools.len() as u64);
+        for tool in tools {
+          let child = tool.keys(matrix, options);
+          hasher.u128(child.key);
+          children.push(child);
+        }
+        children
+      }
+    };
+    Keys {
+      key: hasher.finish(),
+      children,
+    }
+  }
+
+  /// Move the shape out leaving an empty union behind.
+  fn take(&mut self) -> Shape {
+    std::mem::replace(self, Shape::Union(Vec::new()))
+  }
+}
+
+/// The cache key of a shape and the keys of the shapes in it, in the same order.
+struct Keys {
+  key: u128,
+  children: Vec<Keys>,
+}
+
+/// Transform triangles by a matrix.
+///
+/// triangles: The triangles to transform.
+///
+/// matrix: The transformation.
+///
+/// return: The transformed triangles, with their winding reversed if the matrix
+/// mirrors so they still face out.
+fn transformed(triangles: &[Triangle], matrix: Mt4) -> Vec<Triangle> {
+  let mut triangles = triangles.to_vec();
+  triangles.transform(&matrix);
+  triangles
+}
+
+impl From<Mesh> for Shape {
+  fn from(mesh: Mesh) -> Self {
+    Shape::Mesh(mesh)
+  }
+}
+
+impl std::ops::Add for Shape {
+  type Output = Self;
+
+  fn add(self, rhs: Self) -> Self::Output {
+    match (self, rhs) {
+      (Shape::Union(mut shapes), Shape::Union(mut rhs)) => {
+        shapes.append(&mut rhs);
+        Shape::Union(shapes)
+      }
+      (Shape::Union(mut shapes), rhs) => {
+        shapes.push(rhs);
+        Shape::Union(shapes)
+      }
+      (lhs, rhs) => Shape::Union(vec![lhs, rhs]),
+    }
+  }
+}
+
+impl std::ops::AddAssign for Shape {
+  fn add_assign(&mut self, rhs: Self) {
+    *self = self.take() + rhs;
+  }
+}
+
+impl std::ops::Sub for Shape {
+  type Output = Self;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    match self {
+      Shape::Difference(base, mut tools) => {
+        tools.push(rhs);
+        Shape::Difference(base, tools)
+      }
+      lhs => Shape::Difference(Box::new(lhs), vec![rhs]),
+    }
+  }
+}
+
+impl std::ops::SubAssign for Shape {
+  fn sub_assign(&mut self, rhs: Self) {
+    *self = self.take() - rhs;
+  }
+}
+
+impl std::ops::Mul for Shape {
+  type Output = Self;
+
+  fn mul(self, rhs: Self) -> Self::Output {
+    match (self, rhs) {
+      (Shape::Intersection(mut shapes), Shape::Intersection(mut rhs)) => {
+        shapes.append(&mut rhs);
+        Shape::Intersection(shapes)
+      }
+      (Shape::Intersection(mut shapes), rhs) => {
+        shapes.push(rhs);
+        Shape::Intersection(shapes)
+      }
+      (lhs, rhs) => Shape::Intersection(vec![lhs, rhs]),
+    }
+  }
+}
+
+impl std::ops::MulAssign for Shape {
+  fn mul_assign(&mut self, rhs: Self) {
+    *self = self.take() * rhs;
+  }
+}
@@ -0,0 +1,487 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reading and writing of stl files.
+
+use {
+  crate::{Error, Pt3, Result, Triangle},
+  std::io::{BufRead, BufReader, BufWriter, Read, Write},
+};
+
+/// The size of a binary stl header plus the triangle count.
+const BINARY_HEADER: usize = 84;
+
+/// The size of one triangle in a binary stl.
+const BINARY_TRIANGLE: usize = 50;
+
+/// Read ascii or binary stl. Only the first 84 bytes and the length are looked at to
+/// tell them apart, ascii is parsed a line at a time and binary a triangle at a time
+/// so the data is never held in memory all at once.
+///
+/// reader: The source of the bytes.
+///
+/// length: The number of bytes in the data if it is known, like the size of a file.
+///
+/// return: The triangles or an error if reading fails or the data is not stl.
+pub(crate) fn read(mut reader: impl Read, length: Option<u64>) -> Result<Vec<Triangle>> {
+  let mut header = [0; BINARY_HEADER];
+  let read = read_full(&mut reader, &mut header)?;
+  let header = &header[..read];
+  if is_binary(header, length) {
+    read_binary(header, BufReader::new(reader))
+  } else {
+    parse_ascii(BufReader::new(header.chain(reader)))
+  }
+}
+
+/// Decide if stl data is binary. Ascii data starts with "solid", but so do the headers
+/// of some binary files. When the length of the data is known, data that starts with
+/// "solid" is binary only if it is 84 bytes plus 50 for each triangle in the count after
+/// the header.
+///
+/// Otherwise it is binary if the header has a control character in it. The triangle
+/// count has one in its high byte for any count below 150 million, a stray control
+/// character in the name of an ascii solid makes it look binary though.
+///
+/// header: Up to the first 84 bytes of the data.
+///
+/// length: The number of bytes in the data if it is known.
+///
+/// return: True for binary, false for ascii.
+fn is_binary(header: &[u8], length: Option<u64>) -> bool {
+  let start = header
+    .iter()
+    .position(|b| !b.is_ascii_whitespace())
+    .unwrap_or(header.len());
+  let solid = header[start..]
+    .get(..5)
+    .is_some_and(|word| word.eq_ignore_ascii_case(b"solid"));
+  if !solid {
+    return true;
+  }
+  match length {
+    Some(length) => header.len() == BINARY_HEADER && binary_length(header) == length,
+    None => header
+      .iter()
+      .any(|b| b.is_ascii_control() && !b.is_ascii_whitespace()),
+  }
+}
+
+/// The length of binary stl data with the triangle count in a header.
+///
+/// header: The first 84 bytes of the data.
+fn binary_length(header: &[u8]) -> u64 {
+  let count = u32::from_le_bytes([header[80], header[81], header[82], header[83]]);
+  BINARY_HEADER as u64 + count as u64 * BINARY_TRIANGLE as u64
+}
+
+/// Parse ascii stl a line at a time. Keywords are not case sensitive, any amount of
+/// whitespace and either line ending is accepted, the names after solid and endsolid are
+/// skipped, any number of solids are read into one list and facets with more than three
+/// vertices are split into a fan.
+///
+/// reader: The source of the text.
+///
+/// return: The triangles of all the solids or an error if reading fails or the text is
+/// not stl.
+fn parse_ascii(reader: impl BufRead) -> Result<Vec<Triangle>> {
+  let mut tokens = Tokens::new(reader);
+  let mut triangles = Vec::new();
+  let mut vertices: Vec<Pt3> = Vec::new();
+  // whether the loop of the current facet has ended
+  let mut closed = false;
+  while let Some(token) = tokens.next()? {
+    if token.eq_ignore_ascii_case(b"vertex") {
+      vertices.push(Pt3::new(
+        tokens.number()?,
+        tokens.number()?,
+        tokens.number()?,
+      ));
+    } else if token.eq_ignore_ascii_case(b"normal") {
+      // the normal is worked out from the vertices
+      for _ in 0..3 {
+        tokens.number()?;
+      }
+    } else if token.eq_ignore_ascii_case(b"endloop") || token.eq_ignore_ascii_case(b"endfacet") {
+      let endfacet = token.eq_ignore_ascii_case(b"endfacet");
+      // the endfacet after an endloop has nothing left to close
+      if !(endfacet && closed) {
+        if vertices.len() < 3 {
+          return Err(tokens.error("a facet needs at least three vertices"));
+        }
+        for i in 2..vertices.len() {
+          triangles.push(Triangle::new(vertices[0], vertices[i - 1], vertices[i]));
+        }
+        vertices.clear();
+      }
+      closed = !endfacet;
+    } else if token.eq_ignore_ascii_case(b"solid") || token.eq_ignore_ascii_case(b"endsolid") {
+      tokens.skip_line();
+    } else if !(token.eq_ignore_ascii_case(b"facet")
+      || token.eq_ignore_ascii_case(b"outer")
+      || token.eq_ignore_ascii_case(b"loop"))
+    {
+      let token = String::from_utf8_lossy(token).into_owned();
+      return Err(tokens.error(&format!("unexpected \"{}\"", token)));
+    }
+  }
+  if !vertices.is_empty() {
+    return Err(tokens.error("the last facet is not closed"));
+  }
+  Ok(triangles)
+}
+
+/// Read binary stl a triangle at a time.
+///
+/// header: The first 84 bytes of the data, fewer if the data is shorter.
+///
+/// reader: The rest of the data.
+///
+/// return: The triangles or an error if the size of the data doesn't match the triangle
+/// count in the header.
+fn read_binary(header: &[u8], mut reader: impl Read) -> Result<Vec<Triangle>> {
+  if header.len() < BINARY_HEADER {
+    return Err(Error::Truncated {
+      expected: BINARY_HEADER,
+      found: header.len(),
+    });
+  }
+  let count = u32::from_le_bytes([header[80], header[81], header[82], header[83]]) as usize;
+  let expected = count
+    .checked_mul(BINARY_TRIANGLE)
+    .and_then(|size| size.checked_add(BINARY_HEADER));
+  // the count can't be trusted until the data is there
+  let mut triangles = Vec::new();
+  let mut record = [0; BINARY_TRIANGLE];
+  for _ in 0..count {
+    let length = read_full(&mut reader, &mut record)?;
+    if length < BINARY_TRIANGLE {
+      let found = BINARY_HEADER + triangles.len() * BINARY_TRIANGLE + length;
+      return Err(match expected {
+        Some(expected) => Error::Truncated { expected, found },
+        None => Error::TriangleCount {
+          expected: count,
+          found: triangles.len(),
+        },
+      });
+    }
+    // the normal in the first 12 bytes is worked out from the vertices instead
+    let value = |i: usize| {
+      let at = 12 + i * 4;
+      f32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]]) as f64
+    };
+    triangles.push(Triangle::new(
+      Pt3::new(value(0), value(1), value(2)),
+      Pt3::new(value(3), value(4), value(5)),
+      Pt3::new(value(6), value(7), value(8)),
+    ));
+  }
+  let extra = std::io::copy(&mut reader, &mut std::io::sink())? as usize;
+  if extra > 0 {
+    return Err(Error::TriangleCount {
+      expected: count,
+      found: count + extra / BINARY_TRIANGLE,
+    });
+  }
+  Ok(triangles)
+}
+
+/// Fill a buffer from a reader, stopping early only at the end of the data.
+///
+/// return: How many bytes were read.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+  let mut length = 0;
+  while length < buffer.len() {
+    match reader.read(&mut buffer[length..]) {
+      Ok(0) => break,
+      Ok(n) => length += n,
+      Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+      Err(error) => return Err(error),
+    }
+  }
+  Ok(length)
+}
+
+/// Write binary stl with an empty header.
+///
+/// triangles: The triangles to write.
+///
+/// writer: Where the bytes go.
+///
+/// return: An error if writing fails or there are too many triangles for the format.
+pub(crate) fn write_binary(triangles: &[Triangle], writer: impl Write) -> Result<()> {
+  let count = u32::try_from(triangles.len()).map_err(|_| {
+    std::io::Error::new(
+      std::io::ErrorKind::InvalidInput,
+      "binary stl holds at most u32::MAX triangles",
+    )
+  })?;
+  let mut writer = BufWriter::new(writer);
+  writer.write_all(&[0; 80])?;
+  writer.write_all(&count.to_le_bytes())?;
+  for triangle in triangles {
+    let mut record = [0; BINARY_TRIANGLE];
+    let points = [triangle.normal(), triangle.a, triangle.b, triangle.c];
+    for (i, p) in points.iter().enumerate() {
+      for (j, value) in [p.x, p.y, p.z].into_iter().enumerate() {
+        let at = i * 12 + j * 4;
+        record[at..at + 4].copy_from_slice(&(value as f32).to_le_bytes());
+      }
+    }
+    writer.write_all(&record)?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+/// Write ascii stl.
+///
+/// triangles: The triangles to write.
+///
+/// writer: Where the text goes.
+///
+/// name: The name of the solid.
+///
+/// return: An error if writing fails.
+pub(crate) fn write_ascii(triangles: &[Triangle], writer: impl Write, name: &str) -> Result<()> {
+  let mut writer = BufWriter::new(writer);
+  writeln!(writer, "solid {}", name)?;
+  for triangle in triangles {
+    let normal = triangle.normal();
+    writeln!(
+      writer,
+      "facet normal {} {} {}",
+      normal.x as f32, normal.y as f32, normal.z as f32
+    )?;
+    writeln!(writer, "    outer loop")?;
+    for p in [triangle.a, triangle.b, triangle.c] {
+      writeln!(
+        writer,
+        "        vertex {} {} {}",
+        p.x as f32, p.y as f32, p.z as f32
+      )?;
+    }
+    writeln!(writer, "    endloop")?;
+    writeln!(writer, "endfacet")?;
+  }
+  writeln!(writer, "endsolid {}", name)?;
+  writer.flush()?;
+  Ok(())
+}
+
+/// Splits text into whitespace separated tokens, reading one line at a time.
+struct Tokens<R> {
+  reader: R,
+  line: Vec<u8>,
+  position: usize,
+  line_number: usize,
+}
+
+impl<R: BufRead> Tokens<R> {
+  fn new(reader: R) -> Self {
+    Self {
+      reader,
+      line: Vec::new(),
+      position: 0,
+      line_number: 0,
+    }
+  }
+
+  /// The next token or None at the end of the text.
+  fn next(&mut self) -> Result<Option<&[u8]>> {
+    loop {
+      while self.position < self.line.len() && self.line[self.position].is_ascii_whitespace() {
+        self.position += 1;
+      }
+      if self.position < self.line.len() {
+        break;
+      }
+      self.line.clear();
+      self.position = 0;
+      if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+        return Ok(None);
+      }
+      self.line_number += 1;
+    }
+    let start = self.position;
+    while self.position < self.line.len() && !self.line[self.position].is_ascii_whitespace() {
+      self.position += 1;
+    }
+    Ok(Some(&self.line[start..self.position]))
+  }
+
+  /// The next token as a finite number.
+  fn number(&mut self) -> Result<f64> {
+    let number = self
+      .next()?
+      .and_then(|token| std::str::from_utf8(token).ok())
+      .and_then(|token| token.parse::<f64>().ok())
+      .filter(|number| number.is_finite());
+    number.ok_or_else(|| self.error("expected a number"))
+  }
+
+  /// Drop the rest of the current line.
+  fn skip_line(&mut self) {
+    self.position = self.line.len();
+  }
+
+  fn error(&self, message: &str) -> Error {
+    Error::Syntax {
+      line: self.line_number,
+      message: message.to_string(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TRIANGLE: &str = "
+    facet normal 0 0 1
+      outer loop
+        vertex 0 0 0
+        vertex 1 0 0
+        vertex 0 1 0
+      endloop
+    endfacet
+  ";
+
+  /// Describe the result of reading stl data.
+  fn describe(data: &[u8]) -> String {
+    match read(data, None) {
+      Ok(triangles) => format!("{} triangles", triangles.len()),
+      Err(Error::Truncated { expected, found }) => format!("truncated {} {}", expected, found),
+      Err(Error::TriangleCount { expected, found }) => format!("count {} {}", expected, found),
+      Err(Error::Syntax { line, .. }) => format!("syntax {}", line),
+      Err(Error::Io(error)) => format!("io {}", error),
+    }
+  }
+
+  fn cube() -> (Vec<u8>, Vec<u8>) {
+    let cube = crate::Mesh::cube(10.0, 10.0, 10.0, false);
+    let mut binary = Vec::new();
+    write_binary(&cube.triangles, &mut binary).unwrap();
+    let mut ascii = Vec::new();
+    write_ascii(&cube.triangles, &mut ascii, "cube").unwrap();
+    (binary, ascii)
+  }
+
+  #[test]
+  fn round_trips() {
+    let (binary, ascii) = cube();
+    assert_eq!(describe(&binary), "12 triangles");
+    assert_eq!(describe(&ascii), "12 triangles");
+  }
+
+  #[test]
+  fn binary_with_a_solid_header() {
+    let (mut binary, _) = cube();
+    binary[..5].copy_from_slice(b"solid");
+    assert_eq!(describe(&binary), "12 triangles");
+    let length = Some(binary.len() as u64);
+    assert_eq!(read(&binary[..], length).unwrap().len(), 12);
+  }
+
+  #[test]
+  fn ascii_with_a_control_character() {
+    let (_, ascii) = cube();
+    let mut stray = b"solid a\x01b".to_vec();
+    stray.extend_from_slice(&ascii[10..]);
+    // the first bytes look binary, the length says it's not
+    assert_ne!(describe(&stray), "12 triangles");
+    let length = Some(stray.len() as u64);
+    assert_eq!(read(&stray[..], length).unwrap().len(), 12);
+    let mesh = crate::Mesh::read_stl_seekable(std::io::Cursor::new(stray)).unwrap();
+    assert_eq!(mesh.triangles.len(), 12);
+  }
+
+  #[test]
+  fn binary_with_whitespace_in_the_count() {
+    // a text header and a count of 169,877,536 that is all spaces and a line feed
+    let mut header = b"solid".to_vec();
+    header.resize(80, b' ');
+    header.extend_from_slice(b"   \n");
+    let length = binary_length(&header);
+    assert_eq!(length, 84 + 50 * 0x0a20_2020);
+    // the first bytes look ascii, the length says it's not
+    assert!(!is_binary(&header, None));
+    assert!(is_binary(&header, Some(length)));
+    assert!(!is_binary(&header, Some(length - 1)));
+    let (binary, ascii) = cube();
+    let length = |data: &[u8]| Some(data.len() as u64);
+    assert!(is_binary(&binary[..84], length(&binary)));
+    assert!(!is_binary(&ascii[..84], length(&ascii)));
+  }
+
+  #[test]
+  fn damaged_binary() {
+    let (binary, _) = cube();
+    assert_eq!(describe(&[]), "truncated 84 0");
+    assert_eq!(describe(&binary[..40]), "truncated 84 40");
+    assert_eq!(describe(&binary[..binary.len() - 1]), "truncated 684 683");
+    assert_eq!(
+      describe(&[binary.clone(), vec![0; 120]].concat()),
+      "count 12 14"
+    );
+    // a header that claims more triangles than the data holds
+    let mut huge_count = binary;
+    huge_count[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(describe(&huge_count), "truncated 214748364834 684");
+  }
+
+  #[test]
+  fn truncated_ascii() {
+    let (_, ascii) = cube();
+    assert_eq!(describe(&ascii[..ascii.len() / 2]), "syntax 43");
+  }
+
+  fn ascii(name: &str, facets: &str) -> Result<Vec<Triangle>> {
+    read(
+      format!("solid {name}\n{facets}\nendsolid {name}\n").as_bytes(),
+      None,
+    )
+  }
+
+  #[test]
+  fn ascii_with_a_long_name() {
+    let name = "a name that is longer than the binary header, with ümlauts in it";
+    assert_eq!(ascii(name, TRIANGLE).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn facets_with_more_vertices_are_fans() {
+    let quad = TRIANGLE.replace("endloop", "vertex 1 1 0\nendloop");
+    assert_eq!(ascii("quad", &quad).unwrap().len(), 2);
+  }
+
+  #[test]
+  fn facets_with_fewer_vertices_are_errors() {
+    let short = TRIANGLE.replace("vertex 0 1 0", "");
+    match ascii("short", &short) {
+      Err(Error::Syntax { line, .. }) => assert_eq!(line, 8),
+      _ => panic!("a facet with two vertices was read"),
+    }
+    let empty = "facet normal 0 0 1\nendfacet";
+    assert!(matches!(ascii("empty", empty), Err(Error::Syntax { .. })));
+  }
+}
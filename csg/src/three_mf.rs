@@ -0,0 +1,680 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! 3mf files, which hold several named and colored parts in real units and are read by
+//! most slicers. Files are written with the core specification only. The reader only
+//! reads files written by this crate, other programs compress the zip entries of their
+//! files and the reader can't uncompress them.
+
+use {
+  crate::{zip, Error, IndexedMesh, Mesh, Mt4, Pt3, Pt4, Result, Triangle},
+  std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+  },
+};
+
+const MODEL_PATH: &str = "3D/3dmodel.model";
+const MODEL_TYPE: &str = "http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel";
+const CORE_NAMESPACE: &str = "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+ <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+ <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>
+</Types>
+"#;
+
+/// The unit of length of the coordinates in a 3mf file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unit {
+  Micron,
+  #[default]
+  Millimeter,
+  Centimeter,
+  Inch,
+  Foot,
+  Meter,
+}
+
+impl Unit {
+  const ALL: [Unit; 6] = [
+    Unit::Micron,
+    Unit::Millimeter,
+    Unit::Centimeter,
+    Unit::Inch,
+    Unit::Foot,
+    Unit::Meter,
+  ];
+
+  /// The name of the unit in a 3mf file.
+  pub fn name(self) -> &'static str {
+    match self {
+      Unit::Micron => "micron",
+      Unit::Millimeter => "millimeter",
+      Unit::Centimeter => "centimeter",
+      Unit::Inch => "inch",
+      Unit::Foot => "foot",
+      Unit::Meter => "meter",
+    }
+  }
+}
+
+/// A mesh with the name, color and placement it has in an assembly.
+#[derive(Clone)]
+pub struct Part {
+  pub name: String,
+  pub mesh: Mesh,
+  /// Red, green, blue and alpha, None leaves the color to the slicer.
+  pub color: Option<[u8; 4]>,
+  /// Where the mesh is placed, it is stored with the mesh and not applied to it.
+  pub transform: Mt4,
+}
+
+impl Part {
+  /// Creates a part without a color that is placed where the mesh is.
+  ///
+  /// name: The name of the part.
+  ///
+  /// mesh: The mesh.
+  ///
+  /// return: The part.
+  pub fn new(name: &str, mesh: Mesh) -> Self {
+    Self {
+      name: name.to_string(),
+      mesh,
+      color: None,
+      transform: Mt4::identity(),
+    }
+  }
+}
+
+/// Several parts that go to the printer together in one 3mf file.
+#[derive(Clone, Default)]
+pub struct Assembly {
+  pub unit: Unit,
+  pub parts: Vec<Part>,
+}
+
+impl Assembly {
+  /// Creates an empty assembly.
+  ///
+  /// unit: The unit of the coordinates of the meshes.
+  ///
+  /// return: The assembly.
+  pub fn new(unit: Unit) -> Self {
+    Self {
+      unit,
+      parts: Vec::new(),
+    }
+  }
+
+  /// Add a part to the assembly.
+  ///
+  /// part: The part.
+  ///
+  /// return: A mutable reference to the assembly.
+  pub fn add(&mut self, part: Part) -> &mut Self {
+    self.parts.push(part);
+    self
+  }
+
+  /// Saves the assembly as a 3mf file.
+  ///
+  /// path: The path of the file relative to the working directory of the executable.
+  ///
+  /// return: An error if the file can't be written.
+  pub fn save_3mf(&self, path: impl AsRef<Path>) -> Result<()> {
+    self.write_3mf(File::create(path)?)
+  }
+
+  /// Writes the assembly as 3mf.
+  ///
+  /// writer: Where the bytes go.
+  ///
+  /// return: An error if writing fails.
+  pub fn write_3mf(&self, writer: impl Write) -> Result<()> {
+    let rels = format!(
+      concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n",
+        " <Relationship Target=\"/{}\" Id=\"rel0\" Type=\"{}\"/>\n",
+        "</Relationships>\n"
+      ),
+      MODEL_PATH, MODEL_TYPE
+    );
+    zip::write(
+      &[
+        ("[Content_Types].xml", CONTENT_TYPES.as_bytes().to_vec()),
+        ("_rels/.rels", rels.into_bytes()),
+        (MODEL_PATH, self.model().into_bytes()),
+      ],
+      writer,
+    )
+  }
+
+  /// Load a 3mf file.
+  ///
+  /// path: The path of the file relative to the working directory of the executable.
+  ///
+  /// return: The assembly or an error if the file can't be read or is not a 3mf file
+  /// this reader understands.
+  pub fn load_3mf(path: impl AsRef<Path>) -> Result<Self> {
+    Self::read_3mf(File::open(path)?)
+  }
+
+  /// Reads 3mf, see [`Assembly::load_3mf`].
+  ///
+  /// reader: The source of the bytes, it is read to the end.
+  ///
+  /// return: The assembly or an error.
+  pub fn read_3mf(mut reader: impl Read) -> Result<Self> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let files: HashMap<String, Vec<u8>> = zip::read(&data)?.into_iter().collect();
+    // the package relationships say where the model is
+    let mut model_path = MODEL_PATH.to_string();
+    if let Some(rels) = files.get("_rels/.rels") {
+      for event in parse_xml(&String::from_utf8_lossy(rels))? {
+        if let Event::Start(tag) = event {
+          if tag.name == "Relationship" && tag.attribute("Type") == Some(MODEL_TYPE) {
+            if let Some(target) = tag.attribute("Target") {
+              model_path = target.trim_start_matches('/').to_string();
+            }
+          }
+        }
+      }
+    }
+    let model = files
+      .get(&model_path)
+      .ok_or_else(|| invalid(format!("the package has no {}", model_path)))?;
+    Self::parse_model(&String::from_utf8_lossy(model))
+  }
+
+  /// The xml of the model part of the package.
+  fn model(&self) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+      "<model unit=\"{}\" xml:lang=\"en-US\" xmlns=\"{}\">\n",
+      self.unit.name(),
+      CORE_NAMESPACE
+    ));
+    xml.push_str(" <resources>\n");
+    // the colors are one base material group with id 1, the objects follow it
+    let colors: Vec<[u8; 4]> = self.parts.iter().filter_map(|part| part.color).collect();
+    if !colors.is_empty() {
+      xml.push_str("  <basematerials id=\"1\">\n");
+      for (part, color) in self
+        .parts
+        .iter()
+        .filter(|part| part.color.is_some())
+        .zip(&colors)
+      {
+        xml.push_str(&format!(
+          "   <base name=\"{}\" displaycolor=\"#{:02X}{:02X}{:02X}{:02X}\"/>\n",
+          escape(&part.name),
+          color[0],
+          color[1],
+          color[2],
+          color[3]
+        ));
+      }
+      xml.push_str("  </basematerials>\n");
+    }
+    let mut color_index = 0;
+    for (i, part) in self.parts.iter().enumerate() {
+      xml.push_str(&format!(
+        "  <object id=\"{}\" name=\"{}\" type=\"model\"",
+        i + 2,
+        escape(&part.name)
+      ));
+      if part.color.is_some() {
+        xml.push_str(&format!(" pid=\"1\" pindex=\"{}\"", color_index));
+        color_index += 1;
+      }
+      xml.push_str(">\n   <mesh>\n    <vertices>\n");
+      let mesh = IndexedMesh::from_mesh(&part.mesh);
+      for v in &mesh.vertices {
+        xml.push_str(&format!(
+          "     <vertex x=\"{}\" y=\"{}\" z=\"{}\"/>\n",
+          v.x, v.y, v.z
+        ));
+      }
+      xml.push_str("    </vertices>\n    <triangles>\n");
+      // 3mf doesn't allow triangles that use a vertex twice
+      for [a, b, c] in &mesh.triangles {
+        if a != b && b != c && c != a {
+          xml.push_str(&format!(
+            "     <triangle v1=\"{}\" v2=\"{}\" v3=\"{}\"/>\n",
+            a, b, c
+          ));
+        }
+      }
+      xml.push_str("    </triangles>\n   </mesh>\n  </object>\n");
+    }
+    xml.push_str(" </resources>\n <build>\n");
+    for (i, part) in self.parts.iter().enumerate() {
+      xml.push_str(&format!("  <item objectid=\"{}\"", i + 2));
+      if part.transform != Mt4::identity() {
+        // 3mf matrices act on row vectors, so the columns are written as rows
+        let m = part.transform;
+        let values = [m.x, m.y, m.z, m.w].map(|c| format!("{} {} {}", c.x, c.y, c.z));
+        xml.push_str(&format!(" transform=\"{}\"", values.join(" ")));
+      }
+      xml.push_str("/>\n");
+    }
+    xml.push_str(" </build>\n</model>\n");
+    xml
+  }
+
+  /// Make an assembly from the xml of a model.
+  fn parse_model(text: &str) -> Result<Self> {
+    let mut assembly = Assembly::default();
+    let mut colors: HashMap<(usize, usize), [u8; 4]> = HashMap::new();
+    let mut objects: HashMap<usize, Part> = HashMap::new();
+    // the base material group and the next index in it
+    let mut group = (0, 0);
+    let mut object: Option<(usize, Part)> = None;
+    // whether the current object has a mesh
+    let mut mesh = false;
+    let mut vertices: Vec<Pt3> = Vec::new();
+    let mut triangles: Vec<Triangle> = Vec::new();
+    for event in parse_xml(text)? {
+      let tag = match event {
+        Event::Start(tag) => tag,
+        Event::End(name) => {
+          if name == "object" {
+            // an object made of components has no mesh and can't be an item
+            if let Some((id, mut part)) = object.take().filter(|_| mesh) {
+              part.mesh = Mesh::from_triangles(std::mem::take(&mut triangles));
+              objects.insert(id, part);
+            }
+            vertices.clear();
+          }
+          continue;
+        }
+      };
+      match tag.name.as_str() {
+        "model" => {
+          if let Some(unit) = tag.attribute("unit") {
+            assembly.unit = Unit::ALL
+              .into_iter()
+              .find(|u| u.name() == unit)
+              .ok_or_else(|| tag.error(format!("unknown unit \"{}\"", unit)))?;
+          }
+        }
+        "basematerials" => group = (tag.index("id")?, 0),
+        "base" => {
+          if let Some(color) = tag.attribute("displaycolor") {
+            colors.insert(
+              group,
+              parse_color(color).ok_or_else(|| tag.error(format!("bad color \"{}\"", color)))?,
+            );
+          }
+          group.1 += 1;
+        }
+        "object" => {
+          // materials are defined before the objects that use them
+          let mut part = Part::new(
+            tag.attribute("name").unwrap_or(""),
+            Mesh::from_triangles(Vec::new()),
+          );
+          if tag.attribute("pid").is_some() {
+            let key = (tag.index("pid")?, tag.index("pindex").unwrap_or(0));
+            part.color = colors.get(&key).copied();
+          }
+          object = Some((tag.index("id")?, part));
+          mesh = false;
+        }
+        "mesh" => mesh = true,
+        "vertex" => vertices.push(Pt3::new(
+          tag.number("x")?,
+          tag.number("y")?,
+          tag.number("z")?,
+        )),
+        "triangle" => {
+          let corner = |key: &str| {
+            let index = tag.index(key)?;
+            vertices
+              .get(index)
+              .copied()
+              .ok_or_else(|| tag.error(format!("vertex {} is out of range", index)))
+          };
+          triangles.push(Triangle::new(corner("v1")?, corner("v2")?, corner("v3")?));
+        }
+        "item" => {
+          let id = tag.index("objectid")?;
+          let mut part = objects
+            .get(&id)
+            .ok_or_else(|| tag.error(format!("object {} is not a mesh", id)))?
+            .clone();
+          if let Some(transform) = tag.attribute("transform") {
+            let values: Vec<f64> = transform
+              .split_ascii_whitespace()
+              .map(|value| value.parse::<f64>())
+              .collect::<std::result::Result<_, _>>()
+              .ok()
+              .filter(|values: &Vec<f64>| values.len() == 12)
+              .ok_or_else(|| tag.error("a transform needs 12 numbers".to_string()))?;
+            let column = |i: usize, w: f64| Pt4::new(values[i], values[i + 1], values[i + 2], w);
+            part.transform = Mt4::new(
+              column(0, 0.0),
+              column(3, 0.0),
+              column(6, 0.0),
+              column(9, 1.0),
+            );
+          }
+          assembly.parts.push(part);
+        }
+        _ => (),
+      }
+    }
+    Ok(assembly)
+  }
+}
+
+/// An error for data that is not what it should be.
+fn invalid(message: String) -> Error {
+  Error::Io(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// Parse a color written as #RRGGBB or #RRGGBBAA.
+fn parse_color(text: &str) -> Option<[u8; 4]> {
+  let hex = text.strip_prefix('#')?;
+  if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+    return None;
+  }
+  let mut color = [255; 4];
+  for (i, channel) in color.iter_mut().take(hex.len() / 2).enumerate() {
+    *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+  }
+  Some(color)
+}
+
+/// Replace the characters that can't appear in an xml attribute.
+fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Turn the entities in an xml attribute back into characters.
+fn unescape(text: &str) -> Option<String> {
+  let mut result = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(at) = rest.find('&') {
+    result.push_str(&rest[..at]);
+    let end = rest[at..].find(';')? + at;
+    let entity = &rest[at + 1..end];
+    let c = match entity {
+      "amp" => '&',
+      "lt" => '<',
+      "gt" => '>',
+      "quot" => '"',
+      "apos" => '\'',
+      _ => {
+        let code = match entity.strip_prefix("#x") {
+          Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+          None => entity.strip_prefix('#')?.parse().ok()?,
+        };
+        char::from_u32(code)?
+      }
+    };
+    result.push(c);
+    rest = &rest[end + 1..];
+  }
+  result.push_str(rest);
+  Some(result)
+}
+
+/// An element of an xml document, text between elements is not kept.
+enum Event {
+  Start(Tag),
+  /// The end of the element with the name, empty elements have one too.
+  End(String),
+}
+
+/// A start tag with its attributes, the namespace prefixes are left off the names.
+struct Tag {
+  name: String,
+  attributes: Vec<(String, String)>,
+  line: usize,
+}
+
+impl Tag {
+  fn attribute(&self, key: &str) -> Option<&str> {
+    self
+      .attributes
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, value)| value.as_str())
+  }
+
+  fn index(&self, key: &str) -> Result<usize> {
+    self
+      .attribute(key)
+      .and_then(|value| value.trim().parse().ok())
+      .ok_or_else(|| self.error(format!("{} needs an index for {}", self.name, key)))
+  }
+
+  fn number(&self, key: &str) -> Result<f64> {
+    self
+      .attribute(key)
+      .and_then(|value| value.trim().parse::<f64>().ok())
+      .filter(|number| number.is_finite())
+      .ok_or_else(|| self.error(format!("{} needs a number for {}", self.name, key)))
+  }
+
+  fn error(&self, message: String) -> Error {
+    Error::Syntax {
+      line: self.line,
+      message,
+    }
+  }
+}
+
+/// Split an xml document into its elements. Comments, processing instructions and
+/// declarations are skipped, nesting is not checked.
+fn parse_xml(text: &str) -> Result<Vec<Event>> {
+  let mut events = Vec::new();
+  let mut line = 1;
+  let mut rest = text;
+  let error = |line: usize, message: &str| Error::Syntax {
+    line,
+    message: message.to_string(),
+  };
+  while let Some(at) = rest.find('<') {
+    line += rest[..at].matches('\n').count();
+    rest = &rest[at..];
+    let close = if rest.starts_with("<!--") {
+      "-->"
+    } else if rest.starts_with("<![CDATA[") {
+      "]]>"
+    } else {
+      ">"
+    };
+    // quoted attribute values may hold a >
+    let end = if close == ">" {
+      let mut quote = None;
+      rest
+        .char_indices()
+        .find(|&(_, c)| match quote {
+          Some(q) if c == q => {
+            quote = None;
+            false
+          }
+          Some(_) => false,
+          None if c == '"' || c == '\'' => {
+            quote = Some(c);
+            false
+          }
+          None => c == '>',
+        })
+        .map(|(i, _)| i)
+    } else {
+      rest.find(close)
+    }
+    .ok_or_else(|| error(line, "unclosed tag"))?;
+    let inside = &rest[1..end];
+    let start_line = line;
+    line += inside.matches('\n').count();
+    rest = &rest[end + close.len()..];
+    if inside.starts_with('?') || inside.starts_with('!') {
+      continue;
+    }
+    if let Some(name) = inside.strip_prefix('/') {
+      events.push(Event::End(local_name(name.trim()).to_string()));
+      continue;
+    }
+    let (inside, empty) = match inside.strip_suffix('/') {
+      Some(inside) => (inside, true),
+      None => (inside, false),
+    };
+    let name_end = inside
+      .find(|c: char| c.is_ascii_whitespace())
+      .unwrap_or(inside.len());
+    let name = local_name(&inside[..name_end]).to_string();
+    let mut attributes = Vec::new();
+    let mut attribute_text = inside[name_end..].trim_start();
+    while !attribute_text.is_empty() {
+      let equals = attribute_text
+        .find('=')
+        .ok_or_else(|| error(start_line, "attribute without a value"))?;
+      let key = attribute_text[..equals].trim().to_string();
+      let value_text = attribute_text[equals + 1..].trim_start();
+      let quote = value_text
+        .chars()
+        .next()
+        .filter(|&c| c == '"' || c == '\'')
+        .ok_or_else(|| error(start_line, "attribute value without quotes"))?;
+      let value_end = value_text[1..]
+        .find(quote)
+        .ok_or_else(|| error(start_line, "unclosed attribute value"))?
+        + 1;
+      let value = unescape(&value_text[1..value_end])
+        .ok_or_else(|| error(start_line, "bad entity in attribute value"))?;
+      attributes.push((key, value));
+      attribute_text = value_text[value_end + 1..].trim_start();
+    }
+    events.push(Event::Start(Tag {
+      name: name.clone(),
+      attributes,
+      line: start_line,
+    }));
+    if empty {
+      events.push(Event::End(name));
+    }
+  }
+  Ok(events)
+}
+
+/// A name without its namespace prefix.
+fn local_name(name: &str) -> &str {
+  name.rsplit(':').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assembly() -> Assembly {
+    let mut assembly = Assembly::new(Unit::Inch);
+    let mut base = Part::new("base & <plate>", Mesh::cube(40.0, 40.0, 4.0, false));
+    base.color = Some([200, 200, 200, 255]);
+    assembly.add(base);
+    let mut peg = Part::new("peg", Mesh::cylinder(3.0, 3.0, 10.0, 24, false));
+    peg.transform = Mt4::translate_matrix(10.0, 20.0, 4.0) * Mt4::rot_z_matrix(30.0);
+    assembly.add(peg);
+    assembly
+  }
+
+  #[test]
+  fn round_trip() {
+    let saved = assembly();
+    let mut data = Vec::new();
+    saved.write_3mf(&mut data).unwrap();
+    let read = Assembly::read_3mf(&data[..]).unwrap();
+    assert_eq!(read.unit, saved.unit);
+    assert_eq!(read.parts.len(), saved.parts.len());
+    for (saved, read) in saved.parts.iter().zip(&read.parts) {
+      assert_eq!(read.name, saved.name);
+      assert_eq!(read.color, saved.color);
+      assert!(read.transform == saved.transform);
+      assert_eq!(read.mesh.triangles.len(), saved.mesh.triangles.len());
+      assert!(saved
+        .mesh
+        .triangles
+        .iter()
+        .zip(&read.mesh.triangles)
+        .all(|(a, b)| a.a == b.a && a.b == b.b && a.c == b.c));
+    }
+  }
+
+  #[test]
+  fn item_of_components_is_an_error() {
+    let model = format!(
+      r#"<model unit="millimeter" xmlns="{}">
+ <resources>
+  <object id="1" type="model">
+   <mesh>
+    <vertices>
+     <vertex x="0" y="0" z="0"/>
+     <vertex x="1" y="0" z="0"/>
+     <vertex x="0" y="1" z="0"/>
+    </vertices>
+    <triangles>
+     <triangle v1="0" v2="1" v3="2"/>
+    </triangles>
+   </mesh>
+  </object>
+  <object id="2" type="model">
+   <components>
+    <component objectid="1"/>
+   </components>
+  </object>
+ </resources>
+ <build>
+  <item objectid="1"/>
+  <item objectid="2"/>
+ </build>
+</model>
+"#,
+      CORE_NAMESPACE
+    );
+    match Assembly::parse_model(&model) {
+      Err(error) => assert!(error.to_string().contains("object 2 is not a mesh")),
+      Ok(_) => panic!("an item of components was read"),
+    }
+  }
+}
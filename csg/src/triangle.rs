@@ -22,7 +22,7 @@
 
 //! Triangles are the building blocks of meshes.
 
-use crate::Pt3;
+use crate::{Mt4, Pt3};
 
 #[derive(Clone, Copy)]
 pub struct Triangle {
@@ -63,6 +63,37 @@ impl Triangle {
     self.b.rotate_z(degrees);
     self.c.rotate_z(degrees);
   }
+
+  /// Transform the triangle by an affine matrix, the winding is reversed if the matrix
+  /// mirrors so the triangle keeps facing out.
+  pub fn transform(&mut self, matrix: &Mt4) {
+    self.transform_winding(matrix, matrix.determinant() < 0.0);
+  }
+
+  pub fn scale(&mut self, factors: Pt3) {
+    self.transform(&Mt4::scale_matrix(factors.x, factors.y, factors.z));
+  }
+
+  pub fn mirror(&mut self, normal: Pt3, point: Pt3) {
+    self.transform(&Mt4::mirror_matrix(normal, point));
+  }
+
+  pub fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) {
+    self.transform(&Mt4::rot_axis_matrix(axis, pivot, degrees));
+  }
+
+  fn transform_winding(&mut self, matrix: &Mt4, flip: bool) {
+    let (a, b, c) = (
+      self.a.transformed(matrix),
+      self.b.transformed(matrix),
+      self.c.transformed(matrix),
+    );
+    *self = if flip {
+      Self::new(a, c, b)
+    } else {
+      Self::new(a, b, c)
+    };
+  }
 }
 
 pub trait VecTriangle {
@@ -70,6 +101,39 @@ pub trait VecTriangle {
   fn rotate_x(&mut self, degrees: f64) -> &mut Self;
   fn rotate_y(&mut self, degrees: f64) -> &mut Self;
   fn rotate_z(&mut self, degrees: f64) -> &mut Self;
+  // the transforms have default bodies so implementors written before they existed
+  // still compile, they work on anything that is a slice of Triangles
+  fn transform(&mut self, matrix: &Mt4) -> &mut Self
+  where
+    Self: AsMut<[Triangle]>,
+  {
+    let flip = matrix.determinant() < 0.0;
+    for t in self.as_mut() {
+      t.transform_winding(matrix, flip);
+    }
+    self
+  }
+
+  fn scale(&mut self, factors: Pt3) -> &mut Self
+  where
+    Self: AsMut<[Triangle]>,
+  {
+    self.transform(&Mt4::scale_matrix(factors.x, factors.y, factors.z))
+  }
+
+  fn mirror(&mut self, normal: Pt3, point: Pt3) -> &mut Self
+  where
+    Self: AsMut<[Triangle]>,
+  {
+    self.transform(&Mt4::mirror_matrix(normal, point))
+  }
+
+  fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) -> &mut Self
+  where
+    Self: AsMut<[Triangle]>,
+  {
+    self.transform(&Mt4::rot_axis_matrix(axis, pivot, degrees))
+  }
 }
 
 impl VecTriangle for Vec<Triangle> {
@@ -0,0 +1,340 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks a mesh for the problems that keep it from being printed.
+//!
+//! A solid for a printer has to be closed, every edge has two triangles that go
+//! opposite ways across it, every vertex has one fan of triangles around it, no
+//! triangle is flat and no two triangles cut through each other. The report says
+//! where each problem is with points, edges and triangle indices so it can be shown
+//! with a Viewer.
+
+use {
+  crate::{orient3d, Aabb, IndexedMesh, Mesh, Pt3, Viewer},
+  std::collections::HashMap,
+};
+
+/// The sine of the largest angle a triangle can have and still count as flat.
+const DEGENERATE_SINE: f64 = 1.0e-12;
+
+/// What is wrong with a mesh, see [`Mesh::validate`]. Vertices are compared exactly.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+  /// Edges with a triangle on only one side.
+  pub open_edges: Vec<(Pt3, Pt3)>,
+  /// Edges shared by more than two triangles.
+  pub non_manifold_edges: Vec<(Pt3, Pt3)>,
+  /// Vertices where the triangles around them form more than one fan.
+  pub non_manifold_vertices: Vec<Pt3>,
+  /// Edges where both triangles go the same way, so one of them is flipped.
+  pub inconsistent_edges: Vec<(Pt3, Pt3)>,
+  /// Indices of the triangles with no area.
+  pub degenerate_triangles: Vec<usize>,
+  /// Pairs of indices of triangles that cut through each other. Triangles that share
+  /// a vertex are not checked against each other, and triangles that lie in the same
+  /// plane and overlap are not reported since no edge passes through the other.
+  pub self_intersections: Vec<(usize, usize)>,
+  /// The number of pieces connected through edges.
+  pub shells: usize,
+}
+
+impl ValidationReport {
+  /// Check a mesh.
+  ///
+  /// mesh: The mesh to check.
+  ///
+  /// return: The report.
+  pub fn new(mesh: &Mesh) -> Self {
+    let indexed = IndexedMesh::from_mesh(mesh);
+    let vertices = &indexed.vertices;
+    let triangles = &indexed.triangles;
+    let mut report = Self::default();
+
+    // the corners of the triangles that use each undirected edge, the first one
+    // seen decides the direction the edge is reported in
+    let mut edges: HashMap<(usize, usize), Vec<(usize, bool)>> = HashMap::new();
+    let mut order = Vec::new();
+    for (t, triangle) in triangles.iter().enumerate() {
+      for k in 0..3 {
+        let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+        if a == b {
+          continue;
+        }
+        let uses = edges.entry((a.min(b), a.max(b))).or_default();
+        if uses.is_empty() {
+          order.push((a, b));
+        }
+        uses.push((t, a < b));
+      }
+    }
+
+    let mut shells = UnionFind::new(triangles.len());
+    let mut fans = UnionFind::new(triangles.len() * 3);
+    let corner = |t: usize, v: usize| t * 3 + triangles[t].iter().position(|&i| i == v).unwrap();
+    for &(a, b) in &order {
+      let uses = &edges[&(a.min(b), a.max(b))];
+      let edge = (vertices[a], vertices[b]);
+      match uses.len() {
+        1 => report.open_edges.push(edge),
+        2 if uses[0].1 == uses[1].1 => report.inconsistent_edges.push(edge),
+        2 => (),
+        _ => report.non_manifold_edges.push(edge),
+      }
+      for pair in uses.windows(2) {
+        let (s, t) = (pair[0].0, pair[1].0);
+        shells.union(s, t);
+        if uses.len() == 2 {
+          fans.union(corner(s, a), corner(t, a));
+          fans.union(corner(s, b), corner(t, b));
+        }
+      }
+    }
+    report.shells = (0..triangles.len())
+      .filter(|&t| shells.find(t) == t)
+      .count();
+
+    // every fan around a vertex ends up as one set of corners
+    let mut vertex_fans: Vec<Option<usize>> = vec![None; vertices.len()];
+    let mut non_manifold = vec![false; vertices.len()];
+    for (t, triangle) in triangles.iter().enumerate() {
+      for (k, &v) in triangle.iter().enumerate() {
+        let fan = fans.find(t * 3 + k);
+        match vertex_fans[v] {
+          None => vertex_fans[v] = Some(fan),
+          Some(f) if f != fan && !non_manifold[v] => {
+            non_manifold[v] = true;
+            report.non_manifold_vertices.push(vertices[v]);
+          }
+          _ => (),
+        }
+      }
+    }
+
+    let mut flat = vec![false; triangles.len()];
+    for (t, triangle) in mesh.triangles.iter().enumerate() {
+      let longest = (triangle.b - triangle.a)
+        .len2()
+        .max((triangle.c - triangle.b).len2())
+        .max((triangle.a - triangle.c).len2());
+      if triangle.normal().len() <= DEGENERATE_SINE * longest {
+        flat[t] = true;
+        report.degenerate_triangles.push(t);
+      }
+    }
+
+    report.self_intersections = self_intersections(&indexed, &flat);
+    report
+  }
+
+  /// Whether the mesh is a closed manifold without any of the problems.
+  pub fn is_valid(&self) -> bool {
+    self.open_edges.is_empty()
+      && self.non_manifold_edges.is_empty()
+      && self.non_manifold_vertices.is_empty()
+      && self.inconsistent_edges.is_empty()
+      && self.degenerate_triangles.is_empty()
+      && self.self_intersections.is_empty()
+  }
+
+  /// Add the problems to a viewer. Problem edges are added as edges, problem vertices
+  /// as points and problem triangles as their three edges.
+  ///
+  /// mesh: The mesh the report is for.
+  ///
+  /// viewer: The viewer to add to.
+  pub fn add_to_viewer(&self, mesh: &Mesh, viewer: &mut Viewer) {
+    for edges in [
+      &self.open_edges,
+      &self.non_manifold_edges,
+      &self.inconsistent_edges,
+    ] {
+      viewer.add_edges(edges.clone());
+    }
+    viewer.add_pt3s(self.non_manifold_vertices.clone());
+    let pairs = self.self_intersections.iter().flat_map(|&(s, t)| [s, t]);
+    for t in self.degenerate_triangles.iter().copied().chain(pairs) {
+      let triangle = mesh.triangles[t];
+      viewer.add_edges(vec![
+        (triangle.a, triangle.b),
+        (triangle.b, triangle.c),
+        (triangle.c, triangle.a),
+      ]);
+    }
+  }
+}
+
+/// Disjoint sets of indices with path halving.
+struct UnionFind {
+  parents: Vec<usize>,
+}
+
+impl UnionFind {
+  fn new(n: usize) -> Self {
+    Self {
+      parents: (0..n).collect(),
+    }
+  }
+
+  fn find(&mut self, mut i: usize) -> usize {
+    while self.parents[i] != i {
+      self.parents[i] = self.parents[self.parents[i]];
+      i = self.parents[i];
+    }
+    i
+  }
+
+  fn union(&mut self, a: usize, b: usize) {
+    let (a, b) = (self.find(a), self.find(b));
+    // the smaller index is the root so the result doesn't depend on the order
+    self.parents[a.max(b)] = a.min(b);
+  }
+}
+
+/// Find the pairs of triangles that cut through each other. The boxes around the
+/// triangles go into a bounding volume hierarchy and only triangles with overlapping
+/// boxes are tested, so a few large triangles don't make it slow.
+fn self_intersections(mesh: &IndexedMesh, skip: &[bool]) -> Vec<(usize, usize)> {
+  let points = |t: usize| mesh.triangles[t].map(|i| mesh.vertices[i]);
+  let mut boxes: Vec<(usize, Aabb)> = (0..mesh.triangles.len())
+    .filter(|&t| !skip[t])
+    .map(|t| (t, Aabb::from_points(points(t)).unwrap()))
+    .collect();
+  if boxes.is_empty() {
+    return Vec::new();
+  }
+  let mut tree = Vec::new();
+  let root = build_tree(&mut tree, &mut boxes);
+
+  let mut pairs = Vec::new();
+  let mut stack = Vec::new();
+  for &(s, s_box) in &boxes {
+    stack.push(root);
+    while let Some(n) = stack.pop() {
+      match &tree[n] {
+        Node::Branch {
+          bounds,
+          left,
+          right,
+        } => {
+          if bounds.intersection(&s_box).is_some() {
+            stack.push(*left);
+            stack.push(*right);
+          }
+        }
+        Node::Leaf { bounds, triangles } => {
+          if bounds.intersection(&s_box).is_none() {
+            continue;
+          }
+          for &(t, t_box) in triangles {
+            // test each pair once
+            if t <= s || t_box.intersection(&s_box).is_none() {
+              continue;
+            }
+            if mesh.triangles[s]
+              .iter()
+              .any(|v| mesh.triangles[t].contains(v))
+            {
+              continue;
+            }
+            if triangles_cross(points(s), points(t)) {
+              pairs.push((s, t));
+            }
+          }
+        }
+      }
+    }
+  }
+  pairs.sort_unstable();
+  pairs
+}
+
+/// A node of the bounding volume hierarchy in [`self_intersections`].
+enum Node {
+  Branch {
+    bounds: Aabb,
+    left: usize,
+    right: usize,
+  },
+  Leaf {
+    bounds: Aabb,
+    triangles: Vec<(usize, Aabb)>,
+  },
+}
+
+/// Build the hierarchy for some triangle boxes by splitting them in half along the
+/// longest side of the box around their centers.
+///
+/// return: The index of the root node in tree.
+fn build_tree(tree: &mut Vec<Node>, boxes: &mut [(usize, Aabb)]) -> usize {
+  let bounds = boxes[1..]
+    .iter()
+    .fold(boxes[0].1, |bounds, (_, b)| bounds.union(b));
+  if boxes.len() <= 4 {
+    tree.push(Node::Leaf {
+      bounds,
+      triangles: boxes.to_vec(),
+    });
+    return tree.len() - 1;
+  }
+  let centers = Aabb::from_points(boxes.iter().map(|(_, b)| b.center())).unwrap();
+  let size = centers.size();
+  let axis = if size.x >= size.y && size.x >= size.z {
+    0
+  } else if size.y >= size.z {
+    1
+  } else {
+    2
+  };
+  let middle = boxes.len() / 2;
+  boxes.select_nth_unstable_by(middle, |(_, a), (_, b)| {
+    a.center()[axis].total_cmp(&b.center()[axis])
+  });
+  let (low, high) = boxes.split_at_mut(middle);
+  let left = build_tree(tree, low);
+  let right = build_tree(tree, high);
+  tree.push(Node::Branch {
+    bounds,
+    left,
+    right,
+  });
+  tree.len() - 1
+}
+
+/// Whether an edge of either triangle passes through the inside of the other.
+fn triangles_cross(s: [Pt3; 3], t: [Pt3; 3]) -> bool {
+  (0..3).any(|k| segment_crosses(s[k], s[(k + 1) % 3], t))
+    || (0..3).any(|k| segment_crosses(t[k], t[(k + 1) % 3], s))
+}
+
+/// Whether the segment pq passes through the inside of the triangle, with exact
+/// predicates.
+fn segment_crosses(p: Pt3, q: Pt3, [a, b, c]: [Pt3; 3]) -> bool {
+  let sp = orient3d(a, b, c, p);
+  let sq = orient3d(a, b, c, q);
+  if !((sp > 0.0 && sq < 0.0) || (sp < 0.0 && sq > 0.0)) {
+    return false;
+  }
+  let ab = orient3d(p, q, a, b);
+  let bc = orient3d(p, q, b, c);
+  let ca = orient3d(p, q, c, a);
+  (ab > 0.0 && bc > 0.0 && ca > 0.0) || (ab < 0.0 && bc < 0.0 && ca < 0.0)
+}
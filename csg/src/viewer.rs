@@ -124,14 +124,7 @@ impl Viewer {
         self.segments,
         false,
       );
-      for tri in &mut c.triangles {
-        tri.a = (m * tri.a.as_pt4(1.0)).as_pt3();
-        tri.a += edge.0;
-        tri.b = (m * tri.b.as_pt4(1.0)).as_pt3();
-        tri.b += edge.0;
-        tri.c = (m * tri.c.as_pt4(1.0)).as_pt3();
-        tri.c += edge.0;
-      }
+      c.transform(&m).translate(edge.0);
       mesh.triangles.append(&mut c.triangles);
     }
 
@@ -0,0 +1,127 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Finding the points that are the same vertex.
+//!
+//! Everything that turns loose triangles or polygons into connected geometry needs to
+//! know which points are one vertex. Exactly equal points are looked up by their bits,
+//! points that are only close go through a grid with cells the size of the tolerance
+//! so welding is linear in the number of points.
+
+use {crate::Pt3, std::collections::HashMap};
+
+/// A hashable key for a float that is the same for -0.0 and 0.0.
+pub(crate) fn float_key(value: f64) -> u64 {
+  // adding 0.0 turns -0.0 into 0.0
+  (value + 0.0).to_bits()
+}
+
+/// A hashable key for a point, points get the same key only if they are exactly
+/// equal.
+pub(crate) fn vertex_key(p: Pt3) -> [u64; 3] {
+  [float_key(p.x), float_key(p.y), float_key(p.z)]
+}
+
+/// Welds points that are within tolerance of each other into one vertex.
+pub(crate) struct Welder {
+  tolerance: f64,
+  /// The welded vertices in the order they were first seen.
+  vertices: Vec<Pt3>,
+  /// Points by their key, the same point always gets the same index.
+  exact: HashMap<[u64; 3], usize>,
+  /// Vertices by the cell of a grid with cells the size of the tolerance, empty when
+  /// the tolerance is zero.
+  cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl Welder {
+  /// tolerance: How close points have to be to weld, 0.0 only welds equal points.
+  pub(crate) fn new(tolerance: f64) -> Self {
+    Self {
+      tolerance,
+      vertices: Vec::new(),
+      exact: HashMap::new(),
+      cells: HashMap::new(),
+    }
+  }
+
+  /// The index of the vertex for point, adding one if no vertex is within tolerance.
+  pub(crate) fn index(&mut self, point: Pt3) -> usize {
+    let key = vertex_key(point);
+    if let Some(&i) = self.exact.get(&key) {
+      return i;
+    }
+    let i = if self.tolerance > 0.0 {
+      let cell = [
+        (point.x / self.tolerance).floor() as i64,
+        (point.y / self.tolerance).floor() as i64,
+        (point.z / self.tolerance).floor() as i64,
+      ];
+      match self.near(point, cell) {
+        Some(i) => i,
+        None => {
+          self.vertices.push(point);
+          self
+            .cells
+            .entry(cell)
+            .or_default()
+            .push(self.vertices.len() - 1);
+          self.vertices.len() - 1
+        }
+      }
+    } else {
+      self.vertices.push(point);
+      self.vertices.len() - 1
+    };
+    self.exact.insert(key, i);
+    i
+  }
+
+  /// The position of the vertex for point.
+  pub(crate) fn weld(&mut self, point: Pt3) -> Pt3 {
+    let i = self.index(point);
+    self.vertices[i]
+  }
+
+  /// return: The welded vertices, indexed by what index returned.
+  pub(crate) fn into_vertices(self) -> Vec<Pt3> {
+    self.vertices
+  }
+
+  /// The first vertex within tolerance of point in the cells around cell.
+  fn near(&self, point: Pt3, cell: [i64; 3]) -> Option<usize> {
+    for dx in -1..=1 {
+      for dy in -1..=1 {
+        for dz in -1..=1 {
+          if let Some(indices) = self.cells.get(&[cell[0] + dx, cell[1] + dy, cell[2] + dz]) {
+            for &i in indices {
+              if (self.vertices[i] - point).len2() <= self.tolerance * self.tolerance {
+                return Some(i);
+              }
+            }
+          }
+        }
+      }
+    }
+    None
+  }
+}
@@ -0,0 +1,199 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Just enough of the zip format for 3mf files. Entries are written without compression
+//! and only entries without compression can be read back.
+
+use {
+  crate::{Error, Result},
+  std::io::{self, Write},
+};
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+/// The size of the end of central directory record without its comment.
+const END_SIZE: usize = 22;
+/// Version 2.0, the first one with folders.
+const VERSION: u16 = 20;
+/// 1980-01-01, the earliest date a zip file can hold.
+const DATE: u16 = (1 << 5) | 1;
+
+/// Write files into a zip archive.
+///
+/// files: The names and contents of the files, folders are made from the slashes in
+/// the names.
+///
+/// writer: Where the archive goes.
+///
+/// return: An error if writing fails or the archive would need the 64 bit format.
+pub(crate) fn write(files: &[(&str, Vec<u8>)], writer: impl Write) -> Result<()> {
+  let too_big = || io::Error::new(io::ErrorKind::InvalidInput, "zip archive is over 4GB");
+  let mut writer = io::BufWriter::new(writer);
+  let mut central = Vec::new();
+  let mut offset: usize = 0;
+  for (name, data) in files {
+    let size = u32::try_from(data.len()).map_err(|_| too_big())?;
+    let crc = crc32(data);
+    let mut header = Vec::new();
+    header.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+    // version needed, flags, method, time, date, crc and sizes
+    for value in [VERSION, 0, 0, 0, DATE] {
+      header.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in [crc, size, size] {
+      header.extend_from_slice(&value.to_le_bytes());
+    }
+    header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes());
+    header.extend_from_slice(name.as_bytes());
+
+    central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+    central.extend_from_slice(&VERSION.to_le_bytes());
+    central.extend_from_slice(&header[4..30]);
+    // comment length, disk, internal and the two halves of the external attributes
+    for value in [0u16, 0, 0, 0, 0] {
+      central.extend_from_slice(&value.to_le_bytes());
+    }
+    central.extend_from_slice(&u32::try_from(offset).map_err(|_| too_big())?.to_le_bytes());
+    central.extend_from_slice(name.as_bytes());
+
+    writer.write_all(&header)?;
+    writer.write_all(data)?;
+    offset += header.len() + data.len();
+  }
+  let count = u16::try_from(files.len())
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many files for zip"))?;
+  writer.write_all(&central)?;
+  writer.write_all(&END_OF_CENTRAL_DIRECTORY.to_le_bytes())?;
+  for value in [0, 0, count, count] {
+    writer.write_all(&u16::to_le_bytes(value))?;
+  }
+  writer.write_all(
+    &u32::try_from(central.len())
+      .map_err(|_| too_big())?
+      .to_le_bytes(),
+  )?;
+  writer.write_all(&u32::try_from(offset).map_err(|_| too_big())?.to_le_bytes())?;
+  writer.write_all(&0u16.to_le_bytes())?;
+  writer.flush()?;
+  Ok(())
+}
+
+/// Read the files out of a zip archive.
+///
+/// data: The bytes of the archive.
+///
+/// return: The names and contents of the files or an error if the archive is damaged or
+/// uses compression.
+pub(crate) fn read(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+  let invalid = |message: &str| Error::Io(io::Error::new(io::ErrorKind::InvalidData, message));
+  let truncated = |expected: usize| Error::Truncated {
+    expected,
+    found: data.len(),
+  };
+  let u16_at = |at: usize| -> Result<usize> {
+    data
+      .get(at..at + 2)
+      .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+      .ok_or_else(|| truncated(at + 2))
+  };
+  let u32_at = |at: usize| -> Result<usize> {
+    data
+      .get(at..at + 4)
+      .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+      .ok_or_else(|| truncated(at + 4))
+  };
+
+  // the end record is last, followed only by a comment of up to 64KB
+  if data.len() < END_SIZE {
+    return Err(truncated(END_SIZE));
+  }
+  let end = (0..=data.len() - END_SIZE)
+    .rev()
+    .take(u16::MAX as usize + 1)
+    .find(|&at| u32_at(at).ok() == Some(END_OF_CENTRAL_DIRECTORY as usize))
+    .ok_or_else(|| invalid("not a zip archive"))?;
+  let count = u16_at(end + 10)?;
+  let mut at = u32_at(end + 16)?;
+
+  let mut files = Vec::with_capacity(count);
+  for _ in 0..count {
+    if u32_at(at)? != CENTRAL_HEADER as usize {
+      return Err(invalid("damaged zip central directory"));
+    }
+    let method = u16_at(at + 10)?;
+    let crc = u32_at(at + 16)?;
+    let size = u32_at(at + 20)?;
+    let name_len = u16_at(at + 28)?;
+    let skip = name_len + u16_at(at + 30)? + u16_at(at + 32)?;
+    let local = u32_at(at + 42)?;
+    let name = data
+      .get(at + 46..at + 46 + name_len)
+      .ok_or_else(|| truncated(at + 46 + name_len))?;
+    let name = String::from_utf8_lossy(name).into_owned();
+    at += 46 + skip;
+
+    if method != 0 {
+      return Err(Error::Io(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+          "{} is compressed, only stored zip entries can be read",
+          name
+        ),
+      )));
+    }
+    if u32_at(local)? != LOCAL_HEADER as usize {
+      return Err(invalid("damaged zip local header"));
+    }
+    let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+    let contents = data
+      .get(start..start + size)
+      .ok_or_else(|| truncated(start + size))?;
+    if crc32(contents) as usize != crc {
+      return Err(invalid("zip entry fails its checksum"));
+    }
+    files.push((name, contents.to_vec()));
+  }
+  Ok(files)
+}
+
+/// The crc32 checksum zip uses, the reflected 0xedb88320 polynomial.
+fn crc32(data: &[u8]) -> u32 {
+  let mut table = [0u32; 256];
+  for (i, entry) in table.iter_mut().enumerate() {
+    let mut c = i as u32;
+    for _ in 0..8 {
+      c = if c & 1 != 0 {
+        0xedb88320 ^ (c >> 1)
+      } else {
+        c >> 1
+      };
+    }
+    *entry = c;
+  }
+  let mut crc = !0u32;
+  for &byte in data {
+    crc = table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+  }
+  !crc
+}
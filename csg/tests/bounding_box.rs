@@ -0,0 +1,67 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the bounding boxes of meshes.
+
+use csg::{Mesh, Obb, Pt3};
+
+/// Whether the box holds the points, the points on its faces may be a rounding
+/// error outside.
+fn holds(obb: Obb, points: &[Pt3]) -> bool {
+  let loose = Obb {
+    half_size: obb.half_size * (1.0 + 1.0e-12),
+    ..obb
+  };
+  points.iter().all(|&p| loose.contains(p))
+}
+
+fn turned(mut mesh: Mesh) -> Mesh {
+  mesh.rotate_about(Pt3::new(1.0, 2.0, 3.0), Pt3::new(4.0, -5.0, 6.0), 37.0);
+  mesh
+}
+
+#[test]
+fn aabb_of_a_box() {
+  let mut mesh = Mesh::cube(10.0, 4.0, 2.0, false);
+  mesh.translate(Pt3::new(1.0, 2.0, 3.0));
+  let aabb = mesh.bounding_box().unwrap();
+  assert_eq!(aabb.min, Pt3::new(1.0, 2.0, 3.0));
+  assert_eq!(aabb.max, Pt3::new(11.0, 6.0, 5.0));
+}
+
+#[test]
+fn obb_of_a_turned_box_is_the_box() {
+  let mesh = turned(Mesh::cube(10.0, 4.0, 2.0, true));
+  let obb = mesh.oriented_bounding_box().unwrap();
+  assert!((obb.volume() - 80.0).abs() < 1.0e-6 * 80.0);
+  assert!(holds(obb, &mesh.vertices()));
+  assert!(mesh.bounding_box().unwrap().size().x > 10.0);
+}
+
+#[test]
+fn obb_of_a_turned_cylinder_is_close_to_the_smallest() {
+  let mesh = turned(Mesh::cylinder(3.0, 3.0, 10.0, 64, true));
+  let obb = mesh.oriented_bounding_box().unwrap();
+  // the smallest box around the cylinder is 6 by 6 by 10
+  assert!(obb.volume() < 1.02 * 360.0);
+  assert!(holds(obb, &mesh.vertices()));
+}
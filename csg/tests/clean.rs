@@ -0,0 +1,87 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the clean pass.
+
+use csg::{Mesh, Pt3, Triangle, CSG};
+
+/// A unit cube with its top cut in two halves along x = 0.5, so the vertices in the
+/// middle of the top edges are T-junctions on the front and back. The right side is a
+/// hair off the other vertices and a flat triangle lies on an edge of the bottom.
+fn cube_with_t_junctions() -> Mesh {
+  let p = |x, y, z| Pt3::new(x, y, z);
+  let off = |x, y, z| Pt3::new(x, y, z) + Pt3::new(2.0e-7, -1.0e-7, 1.5e-7);
+  let triangles = vec![
+    // bottom
+    Triangle::new(p(0.0, 0.0, 0.0), p(0.0, 1.0, 0.0), p(1.0, 1.0, 0.0)),
+    Triangle::new(p(0.0, 0.0, 0.0), p(1.0, 1.0, 0.0), p(1.0, 0.0, 0.0)),
+    Triangle::new(p(0.0, 0.0, 0.0), p(0.5, 0.0, 0.0), p(1.0, 0.0, 0.0)),
+    // top in two halves
+    Triangle::new(p(0.0, 0.0, 1.0), p(0.5, 0.0, 1.0), p(0.5, 1.0, 1.0)),
+    Triangle::new(p(0.0, 0.0, 1.0), p(0.5, 1.0, 1.0), p(0.0, 1.0, 1.0)),
+    Triangle::new(p(0.5, 0.0, 1.0), p(1.0, 0.0, 1.0), p(1.0, 1.0, 1.0)),
+    Triangle::new(p(0.5, 0.0, 1.0), p(1.0, 1.0, 1.0), p(0.5, 1.0, 1.0)),
+    // front and back
+    Triangle::new(p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(1.0, 0.0, 1.0)),
+    Triangle::new(p(0.0, 0.0, 0.0), p(1.0, 0.0, 1.0), p(0.0, 0.0, 1.0)),
+    Triangle::new(p(0.0, 1.0, 0.0), p(1.0, 1.0, 1.0), p(1.0, 1.0, 0.0)),
+    Triangle::new(p(0.0, 1.0, 0.0), p(0.0, 1.0, 1.0), p(1.0, 1.0, 1.0)),
+    // left and right
+    Triangle::new(p(0.0, 0.0, 0.0), p(0.0, 0.0, 1.0), p(0.0, 1.0, 1.0)),
+    Triangle::new(p(0.0, 0.0, 0.0), p(0.0, 1.0, 1.0), p(0.0, 1.0, 0.0)),
+    Triangle::new(off(1.0, 0.0, 0.0), off(1.0, 1.0, 0.0), off(1.0, 1.0, 1.0)),
+    Triangle::new(off(1.0, 0.0, 0.0), off(1.0, 1.0, 1.0), off(1.0, 0.0, 1.0)),
+  ];
+  Mesh::from_triangles(triangles)
+}
+
+#[test]
+fn cleaned_mesh_is_valid() {
+  let mut mesh = cube_with_t_junctions();
+  let report = mesh.validate();
+  assert!(!report.open_edges.is_empty());
+  assert_eq!(report.degenerate_triangles, [2]);
+
+  mesh.clean(1.0e-5);
+  let report = mesh.validate();
+  assert!(report.is_valid(), "{:?}", report.open_edges);
+  assert!((mesh.volume() - 1.0).abs() < 1.0e-6);
+  // the front and back are split at the T-junctions
+  let front = mesh
+    .triangles
+    .iter()
+    .filter(|t| t.a.y == 0.0 && t.b.y == 0.0 && t.c.y == 0.0)
+    .count();
+  assert_eq!(front, 3);
+}
+
+#[test]
+fn cleaned_csg_is_valid() {
+  let mut csg = CSG::from_triangles(cube_with_t_junctions().triangles);
+  csg.clean(1.0e-5);
+  let mesh = Mesh::from_csg(csg);
+  assert!(mesh.validate().is_valid());
+  // a tolerance smaller than the gap leaves the right side apart
+  let mut csg = CSG::from_triangles(cube_with_t_junctions().triangles);
+  csg.clean(1.0e-8);
+  assert!(!Mesh::from_csg(csg).validate().open_edges.is_empty());
+}
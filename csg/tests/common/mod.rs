@@ -0,0 +1,41 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Helpers shared by the checks.
+
+use csg::CSG;
+
+/// The vertices and planes of the polygons as bits, so equal means identical.
+pub fn bits(csg: &CSG) -> Vec<Vec<u64>> {
+  csg
+    .polygons
+    .iter()
+    .map(|polygon| {
+      let n = polygon.plane.normal;
+      [n.x, n.y, n.z, polygon.plane.w]
+        .into_iter()
+        .chain(polygon.vertices.iter().flat_map(|v| [v.x, v.y, v.z]))
+        .map(f64::to_bits)
+        .collect()
+    })
+    .collect()
+}
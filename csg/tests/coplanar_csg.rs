@@ -0,0 +1,108 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Boolean operations on inputs that share coplanar faces. Every result has to be a
+//! closed manifold, each edge used once in each direction by the two triangles on
+//! either side of it.
+
+use csg::{Mesh, Pt3, CSG};
+
+fn moved(mut mesh: Mesh, v: Pt3) -> Mesh {
+  mesh.translate(v);
+  mesh
+}
+
+fn cube() -> Mesh {
+  Mesh::cube(10.0, 10.0, 10.0, false)
+}
+
+fn assert_closed(mesh: &Mesh) {
+  assert!(!mesh.triangles.is_empty());
+  let report = mesh.validate();
+  assert!(
+    report.is_valid(),
+    "open edges: {}, non manifold edges: {}, non manifold vertices: {}, inconsistent edges: {}, \
+     degenerate triangles: {}, self intersections: {}",
+    report.open_edges.len(),
+    report.non_manifold_edges.len(),
+    report.non_manifold_vertices.len(),
+    report.inconsistent_edges.len(),
+    report.degenerate_triangles.len(),
+    report.self_intersections.len()
+  );
+}
+
+#[test]
+fn cube_minus_flush_cube() {
+  assert_closed(&(cube() - moved(Mesh::cube(5.0, 5.0, 10.0, false), Pt3::new(2.5, 2.5, 0.0))));
+}
+
+#[test]
+fn cube_minus_corner_cube() {
+  assert_closed(&(cube() - Mesh::cube(5.0, 5.0, 5.0, false)));
+}
+
+#[test]
+fn cube_plus_touching_cube() {
+  assert_closed(&(cube() + moved(cube(), Pt3::new(10.0, 2.0, 3.0))));
+}
+
+#[test]
+fn cube_plus_same_cube() {
+  assert_closed(&(cube() + cube()));
+}
+
+#[test]
+fn cube_times_offset_cube() {
+  assert_closed(&(cube() * moved(cube(), Pt3::new(5.0, 0.0, 0.0))));
+}
+
+#[test]
+fn tube() {
+  assert_closed(
+    &(Mesh::cylinder(10.0, 10.0, 10.0, 36, false) - Mesh::cylinder(5.0, 5.0, 10.0, 36, false)),
+  );
+}
+
+#[test]
+fn sphere_minus_cube() {
+  assert_closed(&(Mesh::sphere(10.0, 24) - cube()));
+}
+
+#[test]
+fn exact_at_small_and_large_scales() {
+  for scale in [1.0e-4, 1.0e4] {
+    let scaled = |mut mesh: Mesh| CSG::from_mesh(mesh.scale_uniform(scale).clone());
+    let flush = moved(Mesh::cube(5.0, 5.0, 10.0, false), Pt3::new(2.5, 2.5, 0.0));
+    let mut sphere = Mesh::sphere(8.0, 20);
+    sphere.rotate_x(17.0).translate(Pt3::new(7.0, 1.0, 2.0));
+    let results = [
+      scaled(cube()).subtract_exact(scaled(flush)),
+      scaled(cube()).union_exact(scaled(moved(cube(), Pt3::new(10.0, 2.0, 3.0)))),
+      scaled(cube()).intersect_exact(scaled(moved(cube(), Pt3::new(5.0, 0.0, 0.0)))),
+      scaled(Mesh::sphere(10.0, 24)).union_exact(scaled(sphere)),
+    ];
+    for result in results {
+      assert_closed(&Mesh::from_csg(result));
+    }
+  }
+}
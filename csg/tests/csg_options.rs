@@ -0,0 +1,117 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the options of the boolean operations.
+
+use csg::{CsgOptions, Mesh, Pt3, Tolerance, CSG};
+
+fn cubes() -> (CSG, CSG) {
+  let mut b = Mesh::cube(10.0, 10.0, 10.0, false);
+  b.translate(Pt3::new(5.0, 5.0, 5.0));
+  (
+    CSG::from_mesh(Mesh::cube(10.0, 10.0, 10.0, false)),
+    CSG::from_mesh(b),
+  )
+}
+
+#[test]
+#[should_panic(expected = "merge distance")]
+fn zero_merge_distance_is_rejected() {
+  let (a, b) = cubes();
+  let options = CsgOptions {
+    merge_distance: Tolerance::Absolute(0.0),
+    ..Default::default()
+  };
+  a.union_with(b, &options);
+}
+
+#[test]
+#[should_panic(expected = "snap grid")]
+fn zero_snap_grid_is_rejected() {
+  let (a, b) = cubes();
+  let options = CsgOptions {
+    snap_grid: Some(0.0),
+    ..Default::default()
+  };
+  a.union_with(b, &options);
+}
+
+#[test]
+#[should_panic(expected = "tolerance")]
+fn nan_tolerance_is_rejected() {
+  let (a, b) = cubes();
+  let options = CsgOptions {
+    tolerance: Tolerance::Relative(f64::NAN),
+    ..Default::default()
+  };
+  a.subtract_with(b, &options);
+}
+
+#[test]
+fn relative_tolerances_match_absolute_ones() {
+  // the diagonal of the box around both cubes is 15 * sqrt(3)
+  let size = 15.0 * 3.0f64.sqrt();
+  let relative = CsgOptions {
+    tolerance: Tolerance::Relative(1.0e-5 / size),
+    merge_distance: Tolerance::Relative(1.0e-5 / size),
+    ..Default::default()
+  };
+  let (a, b) = cubes();
+  let expected = a.union(b).into_triangles().len();
+  let (a, b) = cubes();
+  assert_eq!(a.union_with(b, &relative).into_triangles().len(), expected);
+}
+
+/// A block with a turned cylinder through it and a slot a thousandth of its size
+/// wide, measured in millimetres times scale.
+fn block(scale: f64) -> (CSG, CSG) {
+  let block = Mesh::cube(10.0 * scale, 10.0 * scale, 10.0 * scale, false);
+  let mut cylinder = Mesh::cylinder(3.0 * scale, 3.0 * scale, 20.0 * scale, 24, true);
+  cylinder.rotate_about(Pt3::new(1.0, 2.0, 0.5), Pt3::new(0.0, 0.0, 0.0), 25.0);
+  cylinder.translate(Pt3::new(5.0, 5.0, 5.0) * scale);
+  let mut slot = Mesh::cube(0.01 * scale, 20.0 * scale, 4.0 * scale, false);
+  slot.translate(Pt3::new(2.0, -5.0, 8.0) * scale);
+  let tools = CSG::from_mesh(cylinder).union_exact(CSG::from_mesh(slot));
+  (CSG::from_mesh(block), tools)
+}
+
+#[test]
+fn exact_mode_classifies_the_same_at_any_scale() {
+  let (block_mm, tools_mm) = block(1.0);
+  let mm = block_mm.subtract_exact(tools_mm).into_triangles();
+  let (block_um, tools_um) = block(1.0e-3);
+  let um = block_um.subtract_exact(tools_um).into_triangles();
+  assert_eq!(mm.len(), um.len());
+  for (a, b) in mm.iter().zip(&um) {
+    for (p, q) in [(a.a, b.a), (a.b, b.b), (a.c, b.c)] {
+      assert!((p * 1.0e-3 - q).len() < 1.0e-12, "{p} doesn't match {q}");
+    }
+  }
+  let volume_mm = Mesh::from_triangles(mm).volume();
+  let volume_um = Mesh::from_triangles(um.clone()).volume();
+  assert!((volume_mm * 1.0e-9 - volume_um).abs() < 1.0e-9 * volume_um);
+
+  // the default absolute tolerance is as wide as the slot at micron scale
+  let (block_um, tools_um) = block(1.0e-3);
+  let default = block_um.subtract(tools_um).into_triangles();
+  assert_ne!(default.len(), um.len());
+}
@@ -0,0 +1,138 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks that setting aside the parts of the operands outside the overlap of their
+//! bounding boxes doesn't change the booleans.
+
+mod common;
+
+use {
+  common::bits,
+  csg::{BSPNode, Mesh, Pt2, Pt3, CSG},
+};
+
+/// The booleans on whole BSP trees, the way they were done before the culling.
+fn unculled(a: &CSG, b: &CSG, subtract: bool) -> CSG {
+  let mut a = Box::new(BSPNode::new(Some(a.polygons.clone())));
+  let mut b = Box::new(BSPNode::new(Some(b.polygons.clone())));
+  if subtract {
+    a.invert();
+  }
+  a.clip_to(&mut b);
+  b.clip_to(&mut a);
+  b.invert();
+  b.clip_to(&mut a);
+  b.invert();
+  a.build(b.all_polygons());
+  if subtract {
+    a.invert();
+  }
+  CSG {
+    polygons: a.all_polygons(),
+  }
+}
+
+/// An extruded U, the notch is 2 wide and 3 deep.
+fn u_shape() -> CSG {
+  let profile = [
+    Pt2::new(0.0, 0.0),
+    Pt2::new(6.0, 0.0),
+    Pt2::new(6.0, 5.0),
+    Pt2::new(4.0, 5.0),
+    Pt2::new(4.0, 2.0),
+    Pt2::new(2.0, 2.0),
+    Pt2::new(2.0, 5.0),
+    Pt2::new(0.0, 5.0),
+  ];
+  CSG::from_mesh(Mesh::linear_extrude(&profile, 4.0))
+}
+
+fn cube(size: f64, at: Pt3) -> CSG {
+  let mut cube = Mesh::cube(size, size, size, false);
+  cube.translate(at);
+  CSG::from_mesh(cube)
+}
+
+fn sphere(at: Pt3) -> CSG {
+  let mut sphere = Mesh::sphere(2.0, 24);
+  sphere.translate(at);
+  CSG::from_mesh(sphere)
+}
+
+/// The volume and area of a result, which don't depend on how its faces are cut up.
+fn measure(csg: CSG) -> (f64, f64) {
+  let mesh = Mesh::from_csg(csg);
+  (mesh.volume(), mesh.surface_area())
+}
+
+fn assert_same(culled: CSG, unculled: CSG) {
+  let mesh = Mesh::from_csg(culled.clone());
+  assert!(mesh.validate().is_valid());
+  let (volume, area) = measure(culled);
+  let (expected_volume, expected_area) = measure(unculled);
+  assert!((volume - expected_volume).abs() < 1.0e-9 * expected_volume);
+  assert!((area - expected_area).abs() < 1.0e-9 * expected_area);
+}
+
+#[test]
+fn partly_overlapping_operands() {
+  let pairs = [
+    // a corner of a cube in a sphere
+    (
+      cube(4.0, Pt3::new(0.0, 0.0, 0.0)),
+      sphere(Pt3::new(4.0, 4.0, 4.0)),
+    ),
+    // a cube across an arm of the U
+    (u_shape(), cube(2.0, Pt3::new(3.0, 3.0, 1.0))),
+    // a sphere over the end of the notch
+    (u_shape(), sphere(Pt3::new(3.0, 2.0, 4.0))),
+  ];
+  for (a, b) in pairs {
+    assert_same(a.union(b.clone()), unculled(&a, &b, false));
+    assert_same(a.subtract(b.clone()), unculled(&a, &b, true));
+    // a n b = a - (a - b)
+    let a_minus_b = unculled(&a, &b, true);
+    assert_same(a.intersect(b.clone()), unculled(&a, &a_minus_b, true));
+  }
+}
+
+#[test]
+fn operand_in_a_notch() {
+  // the boxes overlap but the surfaces don't touch
+  let (a, b) = (u_shape(), cube(1.0, Pt3::new(2.5, 3.0, 1.0)));
+  let (u_volume, _) = measure(a.clone());
+  let union = a.union(b.clone());
+  assert!((measure(union.clone()).0 - (u_volume + 1.0)).abs() < 1.0e-9);
+  assert_same(union, unculled(&a, &b, false));
+  assert_same(a.subtract(b.clone()), unculled(&a, &b, true));
+  assert!(a.intersect(b).polygons.is_empty());
+}
+
+#[test]
+fn disjoint_operands() {
+  let a = u_shape();
+  let b = sphere(Pt3::new(3.0, 3.0, 10.0));
+  let concatenation: Vec<Vec<u64>> = [bits(&a), bits(&b)].concat();
+  assert!(bits(&a.union(b.clone())) == concatenation);
+  assert!(bits(&a.subtract(b.clone())) == bits(&a));
+  assert!(a.intersect(b).polygons.is_empty());
+}
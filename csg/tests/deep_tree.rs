@@ -0,0 +1,55 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The BSP trees are built, clipped, inverted and dropped without recursion, so a
+//! tree thousands of levels deep works on a thread with a small stack.
+
+use csg::{BSPNode, CsgOptions, Mesh, Pt3, SplitStrategy, CSG};
+
+/// Options that make a tree as deep as a convex mesh has faces. Every polygon of a
+/// convex mesh is behind the plane of every other one, so splitting by the plane of
+/// the first polygon only takes that face off the list at each level.
+fn deep() -> CsgOptions {
+  CsgOptions {
+    split_strategy: SplitStrategy::First,
+    ..Default::default()
+  }
+}
+
+#[test]
+fn deep_tree() {
+  let sphere = CSG::from_mesh(Mesh::sphere(10.0, 80));
+  let n_polygons = sphere.polygons.len();
+  let mut tree = BSPNode::with_options(sphere.polygons.clone(), &deep());
+  assert_eq!(tree.all_polygons().len(), n_polygons);
+  tree.invert();
+  // inverted, the sphere keeps what is outside of it and clips away itself
+  assert!(tree.clip_polygons(sphere.polygons).is_empty());
+}
+
+#[test]
+fn deep_tree_boolean() {
+  let mut cube = Mesh::cube(20.0, 20.0, 20.0, true);
+  cube.translate(Pt3::new(10.0, 0.0, 0.0));
+  let half = CSG::from_mesh(Mesh::sphere(10.0, 80)).subtract_with(CSG::from_mesh(cube), &deep());
+  assert!(Mesh::from_csg(half).validate().open_edges.is_empty());
+}
@@ -0,0 +1,193 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the half-edge mesh.
+
+use {
+  csg::{HalfEdgeMesh, IndexedMesh, Mesh, Pt3, TopologyError},
+  std::collections::HashSet,
+};
+
+fn cube() -> IndexedMesh {
+  IndexedMesh::from_mesh(&Mesh::cube(1.0, 1.0, 1.0, false))
+}
+
+/// A cube with the triangles facing up left out.
+fn open_cube() -> IndexedMesh {
+  let mut mesh = cube();
+  let vertices = mesh.vertices.clone();
+  mesh
+    .triangles
+    .retain(|t| t.iter().any(|&v| vertices[v].z < 0.5));
+  mesh
+}
+
+fn index_of(mesh: &IndexedMesh, p: Pt3) -> usize {
+  mesh.vertices.iter().position(|&v| v == p).unwrap()
+}
+
+#[test]
+fn circulators_on_a_cube() {
+  let indexed = cube();
+  let mesh = HalfEdgeMesh::from_indexed(&indexed).unwrap();
+  assert_eq!(mesh.vertices().len(), 8);
+  assert_eq!(mesh.face_count(), 12);
+  assert!(mesh.half_edges().iter().all(|h| h.twin.is_some()));
+  assert_eq!(mesh.to_indexed().triangles, indexed.triangles);
+
+  for face in 0..mesh.face_count() {
+    let vertices = mesh.face_vertices(face);
+    assert_eq!(vertices, indexed.triangles[face]);
+    // each neighbour shares the edge starting at the same corner
+    for (k, neighbour) in mesh.face_neighbours(face).into_iter().enumerate() {
+      let neighbour = mesh.face_vertices(neighbour.unwrap());
+      assert!(neighbour.contains(&vertices[k]));
+      assert!(neighbour.contains(&vertices[(k + 1) % 3]));
+    }
+  }
+
+  for v in 0..8 {
+    assert!(!mesh.is_boundary_vertex(v));
+    let faces: Vec<usize> = mesh.vertex_faces(v).collect();
+    let expected: HashSet<usize> = (0..12)
+      .filter(|&f| indexed.triangles[f].contains(&v))
+      .collect();
+    assert_eq!(faces.len(), expected.len());
+    assert_eq!(faces.iter().copied().collect::<HashSet<_>>(), expected);
+
+    // every corner of a cube has three edges along the sides and some diagonals
+    let ring = mesh.one_ring(v);
+    assert_eq!(ring.len(), faces.len());
+    let axes = ring
+      .iter()
+      .filter(|&&n| (mesh.vertices()[n] - mesh.vertices()[v]).len() == 1.0)
+      .count();
+    assert_eq!(axes, 3);
+    // counter clockwise, each neighbour and the next one make a triangle with v
+    for (i, &n) in ring.iter().enumerate() {
+      let next = ring[(i + 1) % ring.len()];
+      let h = mesh.find_half_edge(v, n).unwrap();
+      let face = mesh.half_edges()[h].face;
+      assert_eq!(faces[i], face);
+      assert!(mesh.face_vertices(face).contains(&next));
+      assert_eq!(mesh.edge_faces(v, n).0, Some(face));
+      assert!(mesh.edge_faces(v, n).1.is_some());
+    }
+  }
+  assert!(mesh.boundary_loops().is_empty());
+}
+
+#[test]
+fn boundary_loops_of_an_open_mesh() {
+  let indexed = open_cube();
+  let mesh = HalfEdgeMesh::from_indexed(&indexed).unwrap();
+  assert_eq!(mesh.face_count(), 10);
+  let loops = mesh.boundary_loops();
+  assert_eq!(loops.len(), 1);
+  let boundary = &loops[0];
+  assert_eq!(boundary.len(), 4);
+  for (i, &v) in boundary.iter().enumerate() {
+    assert_eq!(mesh.vertices()[v].z, 1.0);
+    assert!(mesh.is_boundary_vertex(v));
+    // the loop follows the triangles, so each step is a half-edge without a twin
+    let h = mesh.find_half_edge(v, boundary[(i + 1) % 4]).unwrap();
+    assert!(mesh.half_edges()[h].twin.is_none());
+  }
+  // the one-ring of a boundary vertex has one more vertex than it has triangles
+  for &v in boundary {
+    assert_eq!(mesh.one_ring(v).len(), mesh.vertex_faces(v).count() + 1);
+  }
+
+  // a tube has two loops
+  let mut tube = open_cube();
+  let vertices = tube.vertices.clone();
+  tube
+    .triangles
+    .retain(|t| t.iter().any(|&v| vertices[v].z > 0.5));
+  let mesh = HalfEdgeMesh::from_indexed(&tube).unwrap();
+  let mut heights: Vec<f64> = mesh
+    .boundary_loops()
+    .iter()
+    .map(|boundary| {
+      assert_eq!(boundary.len(), 4);
+      mesh.vertices()[boundary[0]].z
+    })
+    .collect();
+  heights.sort_by(f64::total_cmp);
+  assert_eq!(heights, [0.0, 1.0]);
+}
+
+#[test]
+fn degenerate_face() {
+  let mut mesh = cube();
+  mesh.triangles.push([3, 3, 5]);
+  let error = HalfEdgeMesh::from_indexed(&mesh).err();
+  assert_eq!(error, Some(TopologyError::DegenerateFace(12)));
+}
+
+#[test]
+fn non_manifold_edge() {
+  // three triangles on one edge
+  let mesh = IndexedMesh {
+    vertices: vec![
+      Pt3::new(0.0, 0.0, 0.0),
+      Pt3::new(1.0, 0.0, 0.0),
+      Pt3::new(0.0, 1.0, 0.0),
+      Pt3::new(0.0, -1.0, 0.0),
+      Pt3::new(0.0, 0.0, 1.0),
+    ],
+    triangles: vec![[0, 1, 2], [1, 0, 3], [0, 1, 4]],
+  };
+  let error = HalfEdgeMesh::from_indexed(&mesh).err();
+  assert_eq!(error, Some(TopologyError::NonManifoldEdge(0, 1)));
+  assert_eq!(
+    error.unwrap().to_string(),
+    "edge from vertex 0 to 1 is used by more than one triangle"
+  );
+}
+
+#[test]
+fn non_manifold_vertex() {
+  // two cubes that touch at a corner
+  let mut b = Mesh::cube(1.0, 1.0, 1.0, false);
+  b.translate(Pt3::new(1.0, 1.0, 1.0));
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  mesh.triangles.extend(b.triangles);
+  let indexed = IndexedMesh::from_mesh(&mesh);
+  let corner = index_of(&indexed, Pt3::new(1.0, 1.0, 1.0));
+  let error = HalfEdgeMesh::from_indexed(&indexed).err();
+  assert_eq!(error, Some(TopologyError::NonManifoldVertex(corner)));
+}
+
+#[test]
+fn inconsistent_winding() {
+  // a flipped triangle goes the same way along an edge as its neighbour
+  let mut mesh = cube();
+  mesh.triangles[4].swap(1, 2);
+  let [a, b, c] = mesh.triangles[4];
+  match HalfEdgeMesh::from_indexed(&mesh) {
+    Err(TopologyError::NonManifoldEdge(p, q)) => {
+      assert!([[a, b], [b, c], [c, a]].contains(&[p, q]));
+    }
+    _ => panic!("a flipped triangle was accepted"),
+  }
+}
@@ -0,0 +1,135 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the indexed mesh.
+
+use {
+  csg::{IndexedMesh, MersenneTwister, Mesh, Pt3, Triangle},
+  std::collections::HashSet,
+};
+
+fn same_triangles(a: &[Triangle], b: &[Triangle]) -> bool {
+  a.len() == b.len()
+    && a
+      .iter()
+      .zip(b)
+      .all(|(s, t)| s.a == t.a && s.b == t.b && s.c == t.c)
+}
+
+#[test]
+fn round_trip() {
+  for mesh in [Mesh::cube(1.0, 2.0, 3.0, false), Mesh::sphere(2.0, 24)] {
+    let indexed = IndexedMesh::from_triangles(&mesh.triangles);
+    assert_eq!(indexed.triangles.len(), mesh.triangles.len());
+    assert!(same_triangles(
+      &indexed.to_mesh().triangles,
+      &mesh.triangles
+    ));
+    let back: Mesh = IndexedMesh::from(mesh.clone()).into();
+    assert!(same_triangles(&back.triangles, &mesh.triangles));
+    // every vertex is used and none is stored twice
+    let used: HashSet<usize> = indexed.triangles.iter().flatten().copied().collect();
+    assert_eq!(used.len(), indexed.vertices.len());
+    for (i, a) in indexed.vertices.iter().enumerate() {
+      assert!(indexed.vertices[i + 1..].iter().all(|b| a != b));
+    }
+  }
+  assert_eq!(
+    IndexedMesh::from_mesh(&Mesh::cube(1.0, 2.0, 3.0, false))
+      .vertices
+      .len(),
+    8
+  );
+}
+
+#[test]
+fn welding_within_a_tolerance() {
+  // every corner of each triangle moved by up to a micron
+  let cube = Mesh::cube(1.0, 1.0, 1.0, false);
+  let mut rng = MersenneTwister::with_seed(11);
+  let mut jitter = |p: Pt3| {
+    p + Pt3::new(
+      rng.f64_minmax(-1.0e-6, 1.0e-6),
+      rng.f64_minmax(-1.0e-6, 1.0e-6),
+      rng.f64_minmax(-1.0e-6, 1.0e-6),
+    )
+  };
+  let triangles: Vec<Triangle> = cube
+    .triangles
+    .iter()
+    .map(|t| Triangle::new(jitter(t.a), jitter(t.b), jitter(t.c)))
+    .collect();
+  assert_eq!(IndexedMesh::from_triangles(&triangles).vertices.len(), 36);
+  let welded = IndexedMesh::from_triangles_welded(&triangles, 1.0e-5);
+  assert_eq!(welded.vertices.len(), 8);
+  assert_eq!(welded.triangles.len(), 12);
+  for v in &welded.vertices {
+    let corner = Pt3::new(v.x.round(), v.y.round(), v.z.round());
+    assert!((*v - corner).len() < 2.0e-6);
+  }
+
+  // points just past the tolerance stay apart and collapsed triangles are dropped
+  let a = Pt3::new(0.0, 0.0, 0.0);
+  let b = Pt3::new(1.0, 0.0, 0.0);
+  let triangles = [
+    Triangle::new(a, b, Pt3::new(0.0, 1.0, 0.0)),
+    Triangle::new(a, Pt3::new(0.0, 0.0, 0.5e-3), b),
+    Triangle::new(a, b, Pt3::new(0.0, 0.0, 2.0e-3)),
+  ];
+  let welded = IndexedMesh::from_triangles_welded(&triangles, 1.0e-3);
+  assert_eq!(welded.vertices.len(), 4);
+  assert_eq!(welded.triangles, [[0, 1, 2], [0, 1, 3]]);
+}
+
+#[test]
+fn edges() {
+  let cube = IndexedMesh::from_mesh(&Mesh::cube(1.0, 1.0, 1.0, false));
+  let edges = cube.edges();
+  // twelve sides and a diagonal across each face
+  assert_eq!(edges.len(), 18);
+  let sides = edges
+    .iter()
+    .filter(|[a, b]| (cube.vertices[*a] - cube.vertices[*b]).len() == 1.0)
+    .count();
+  assert_eq!(sides, 12);
+  let unordered: HashSet<(usize, usize)> =
+    edges.iter().map(|&[a, b]| (a.min(b), a.max(b))).collect();
+  assert_eq!(unordered.len(), 18);
+  // in the direction the first triangle that uses them goes
+  let [a, b, c] = cube.triangles[0];
+  assert_eq!(edges[..3], [[a, b], [b, c], [c, a]]);
+  for &[p, q] in &edges {
+    let first = cube
+      .triangles
+      .iter()
+      .find(|t| t.contains(&p) && t.contains(&q))
+      .unwrap();
+    let i = first.iter().position(|&v| v == p).unwrap();
+    assert_eq!(first[(i + 1) % 3], q);
+  }
+  // V - E + F = 2 for a closed mesh like a sphere
+  let sphere = IndexedMesh::from_mesh(&Mesh::sphere(1.0, 16));
+  assert_eq!(
+    sphere.vertices.len() + sphere.triangles.len(),
+    sphere.edges().len() + 2
+  );
+}
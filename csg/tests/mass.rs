@@ -0,0 +1,149 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the volume, area and inertia of meshes.
+
+use {
+  csg::{MassProperties, Mesh, Pt3},
+  std::f64::consts::PI,
+};
+
+fn assert_close(a: f64, b: f64, tolerance: f64) {
+  assert!(
+    (a - b).abs() <= tolerance * b.abs().max(1.0),
+    "{a} is not {b}"
+  );
+}
+
+fn assert_tensor(a: [[f64; 3]; 3], b: [[f64; 3]; 3], tolerance: f64) {
+  for i in 0..3 {
+    for j in 0..3 {
+      assert_close(a[i][j], b[i][j], tolerance);
+    }
+  }
+}
+
+/// A 2 by 3 by 4 box from (10, 20, 30) to (12, 23, 34).
+fn offset_box() -> Mesh {
+  let mut mesh = Mesh::cube(2.0, 3.0, 4.0, false);
+  mesh.translate(Pt3::new(10.0, 20.0, 30.0));
+  mesh
+}
+
+#[test]
+fn offset_box_is_exact() {
+  let mesh = offset_box();
+  assert_close(mesh.volume(), 24.0, 1.0e-12);
+  assert_close(mesh.surface_area(), 52.0, 1.0e-12);
+  let mass = mesh.mass_properties(2.0);
+  assert_close(mass.volume, 24.0, 1.0e-12);
+  assert_close(mass.area, 52.0, 1.0e-12);
+  assert_close(mass.mass, 48.0, 1.0e-12);
+  assert!((mass.center_of_mass - Pt3::new(11.0, 21.5, 32.0)).len() < 1.0e-12);
+  // m / 12 * (b^2 + c^2) for each axis and nothing off the diagonal
+  assert_tensor(
+    mass.inertia,
+    [[100.0, 0.0, 0.0], [0.0, 80.0, 0.0], [0.0, 0.0, 52.0]],
+    1.0e-12,
+  );
+}
+
+#[test]
+fn inward_normals_give_a_negative_volume() {
+  let mut mesh = offset_box();
+  for triangle in &mut mesh.triangles {
+    std::mem::swap(&mut triangle.b, &mut triangle.c);
+  }
+  assert_close(mesh.volume(), -24.0, 1.0e-12);
+  assert_close(mesh.surface_area(), 52.0, 1.0e-12);
+}
+
+#[test]
+fn spheres_converge_to_the_analytic_values() {
+  let r = 2.0;
+  let volume = 4.0 / 3.0 * PI * r * r * r;
+  let area = 4.0 * PI * r * r;
+  let mut last_error = f64::INFINITY;
+  for segments in [16, 32, 64, 128, 256] {
+    let mass = Mesh::sphere(r, segments).mass_properties(1.0);
+    let error = (mass.volume - volume).abs() / volume;
+    // the error is about a quarter for twice the segments
+    assert!(error < last_error * 0.3, "{segments} segments: {error}");
+    last_error = error;
+    assert!(mass.center_of_mass.len() < 1.0e-9);
+    assert!(mass.volume < volume);
+    assert!(mass.area < area);
+  }
+  let mass = Mesh::sphere(r, 256).mass_properties(3.0);
+  assert_close(mass.volume, volume, 1.0e-3);
+  assert_close(mass.area, area, 1.0e-3);
+  assert_close(mass.mass, 3.0 * volume, 1.0e-3);
+  // 2 / 5 m r^2 around any axis through the center
+  let moment = 0.4 * 3.0 * volume * r * r;
+  for i in 0..3 {
+    assert_close(mass.inertia[i][i], moment, 2.0e-3);
+    for j in 0..3 {
+      if i != j {
+        assert!(mass.inertia[i][j].abs() < 1.0e-9 * moment);
+      }
+    }
+  }
+}
+
+#[test]
+fn parallel_axis_theorem() {
+  let mass = offset_box().mass_properties(2.0);
+  // m / 3 * (a^2 + b^2) around an edge of the box
+  assert_close(
+    mass.inertia_about_axis(Pt3::new(10.0, 20.0, 0.0), Pt3::new(0.0, 0.0, 1.0)),
+    208.0,
+    1.0e-12,
+  );
+  // the products of inertia about a corner are -m times the offsets
+  let corner = mass.inertia_about_point(Pt3::new(10.0, 20.0, 30.0));
+  assert_close(corner[0][1], -48.0 * 1.0 * 1.5, 1.0e-12);
+  assert_close(corner[1][2], -48.0 * 1.5 * 2.0, 1.0e-12);
+  assert_close(corner[2][0], -48.0 * 2.0 * 1.0, 1.0e-12);
+
+  // a turned box has products of inertia about its center, moving the axis off the
+  // center adds the mass times the squared distance
+  let mut mesh = offset_box();
+  mesh.rotate_about(Pt3::new(1.0, 2.0, 3.0), Pt3::new(4.0, -5.0, 6.0), 37.0);
+  let mass = MassProperties::new(&mesh, 1.5);
+  assert!(mass.inertia[0][1].abs() > 1.0);
+  let c = mass.center_of_mass;
+  assert_tensor(mass.inertia_about_point(c), mass.inertia, 1.0e-12);
+  let direction = Pt3::new(2.0, -1.0, 0.5);
+  let offset = Pt3::new(3.0, 4.0, -2.0);
+  let distance = offset.cross(direction.normalized()).len();
+  assert_close(
+    mass.inertia_about_axis(c + offset, direction),
+    mass.inertia_about_axis(c, direction) + mass.mass * distance * distance,
+    1.0e-10,
+  );
+  // along the axis itself the point doesn't matter
+  assert_close(
+    mass.inertia_about_axis(c + direction * 5.0, direction),
+    mass.inertia_about_axis(c, direction),
+    1.0e-10,
+  );
+}
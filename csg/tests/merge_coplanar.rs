@@ -0,0 +1,60 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of merging coplanar triangles.
+
+use csg::{Mesh, Pt3, CSG};
+
+/// A cube with a hole through the middle, the top and bottom faces are rings.
+fn cube_with_hole() -> Mesh {
+  let cube = Mesh::cube(20.0, 20.0, 20.0, true);
+  let mut cylinder = Mesh::cylinder(5.0, 5.0, 30.0, 32, true);
+  cylinder.translate(Pt3::new(2.0, 1.0, 0.0));
+  Mesh::from_csg(CSG::from_mesh(cube).subtract(CSG::from_mesh(cylinder)))
+}
+
+/// A cube with a block cut out of one edge, the side faces are L shaped.
+fn cube_with_notch() -> Mesh {
+  let cube = Mesh::cube(20.0, 20.0, 20.0, false);
+  let mut notch = Mesh::cube(10.0, 30.0, 10.0, false);
+  notch.translate(Pt3::new(10.0, -5.0, 10.0));
+  Mesh::from_csg(CSG::from_mesh(cube).subtract(CSG::from_mesh(notch)))
+}
+
+fn assert_merges(mut mesh: Mesh) {
+  assert!(mesh.validate().is_valid());
+  let (triangles, volume) = (mesh.triangles.len(), mesh.volume());
+  mesh.merge_coplanar();
+  assert!(mesh.validate().is_valid());
+  assert!(mesh.triangles.len() < triangles);
+  assert!((mesh.volume() - volume).abs() < 1.0e-9 * volume);
+}
+
+#[test]
+fn faces_with_holes() {
+  assert_merges(cube_with_hole());
+}
+
+#[test]
+fn concave_faces() {
+  assert_merges(cube_with_notch());
+}
@@ -0,0 +1,89 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks that the `parallel` feature gives the same results as one thread.
+//!
+//! On a pool with one thread rayon runs the halves of a join one after the other and
+//! folds the polygons of a split in order, which is the work the serial path does.
+
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use {
+  common::bits,
+  csg::{Mesh, Pt3, CSG},
+};
+
+/// Two spheres with more polygons than are split on one thread.
+fn spheres() -> (CSG, CSG) {
+  let a = Mesh::sphere(10.0, 40);
+  let mut b = Mesh::sphere(8.0, 36);
+  b.translate(Pt3::new(6.0, 3.0, 2.0));
+  assert!(a.triangles.len() > 1024 && b.triangles.len() > 1024);
+  (CSG::from_mesh(a), CSG::from_mesh(b))
+}
+
+fn run(threads: usize, boolean: fn(CSG, CSG) -> CSG) -> Vec<Vec<u64>> {
+  let pool = rayon::ThreadPoolBuilder::new()
+    .num_threads(threads)
+    .build()
+    .unwrap();
+  pool.install(|| {
+    let (a, b) = spheres();
+    bits(&boolean(a, b))
+  })
+}
+
+#[test]
+fn booleans_match_one_thread() {
+  let booleans: [fn(CSG, CSG) -> CSG; 3] = [
+    |a, b| a.union(b),
+    |a, b| a.subtract(b),
+    |a, b| a.intersect(b),
+  ];
+  for boolean in booleans {
+    let serial = run(1, boolean);
+    assert!(!serial.is_empty());
+    assert!(serial == run(8, boolean));
+  }
+}
+
+#[test]
+fn union_all_matches_one_thread() {
+  let union_all = |threads| {
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(threads)
+      .build()
+      .unwrap();
+    pool.install(|| {
+      let cubes = (0..16).map(|i| {
+        let mut cube = Mesh::cube(2.0, 2.0, 2.0, false);
+        cube.translate(Pt3::new(i as f64 * 1.5, (i % 3) as f64, 0.0));
+        CSG::from_mesh(cube)
+      });
+      let (a, b) = spheres();
+      bits(&CSG::union_all(cubes.chain([a, b])))
+    })
+  };
+  assert!(union_all(1) == union_all(8));
+}
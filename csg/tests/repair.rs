@@ -0,0 +1,123 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of Mesh::repair.
+
+use csg::{Mesh, Pt2, Triangle};
+
+/// An extruded L with its top removed, which leaves a hole that isn't convex.
+fn open_l(size: f64) -> Mesh {
+  let profile = [
+    Pt2::new(0.0, 0.0),
+    Pt2::new(2.0, 0.0),
+    Pt2::new(2.0, 1.0),
+    Pt2::new(1.0, 1.0),
+    Pt2::new(1.0, 2.0),
+    Pt2::new(0.0, 2.0),
+  ];
+  let mut mesh = Mesh::linear_extrude(&profile, 1.0);
+  mesh.scale_uniform(size);
+  let top = size;
+  mesh
+    .triangles
+    .retain(|t| !(t.a.z == top && t.b.z == top && t.c.z == top));
+  mesh
+}
+
+#[test]
+fn fills_a_hole() {
+  let mut mesh = open_l(10.0);
+  assert!(!mesh.validate().is_valid());
+  mesh.repair();
+  assert!(mesh.validate().is_valid());
+}
+
+#[test]
+fn fills_a_small_hole() {
+  let mut mesh = open_l(1.0e-3);
+  assert!(!mesh.validate().is_valid());
+  mesh.repair();
+  assert!(mesh.validate().is_valid());
+}
+
+#[test]
+fn fills_a_hole_with_a_straight_corner() {
+  let profile = [
+    Pt2::new(0.0, 0.0),
+    Pt2::new(1.0, 0.0),
+    Pt2::new(2.0, 0.0),
+    Pt2::new(2.0, 1.0),
+    Pt2::new(1.0, 1.0),
+    Pt2::new(1.0, 2.0),
+    Pt2::new(0.0, 1.0),
+    Pt2::new(0.0, 0.5),
+  ];
+  let mut mesh = Mesh::linear_extrude(&profile, 1.0);
+  mesh
+    .triangles
+    .retain(|t| !(t.a.z == 1.0 && t.b.z == 1.0 && t.c.z == 1.0));
+  assert!(!mesh.validate().is_valid());
+  mesh.repair();
+  assert!(mesh.validate().is_valid());
+  assert!((mesh.volume() - 2.5).abs() < 1.0e-12);
+}
+
+#[test]
+fn flips_facets() {
+  let mut mesh = Mesh::cube(1.0, 2.0, 3.0, false);
+  for t in mesh.triangles.iter_mut().step_by(3) {
+    std::mem::swap(&mut t.b, &mut t.c);
+  }
+  assert!(!mesh.validate().is_valid());
+  mesh.repair();
+  assert!(mesh.validate().is_valid());
+  assert!((mesh.volume() - 6.0).abs() < 1.0e-12);
+}
+
+#[test]
+fn turns_inward_normals_out() {
+  let mut mesh = Mesh::sphere(1.0, 16);
+  for t in mesh.triangles.iter_mut() {
+    std::mem::swap(&mut t.b, &mut t.c);
+  }
+  assert!(mesh.volume() < 0.0);
+  mesh.repair();
+  assert!(mesh.validate().is_valid());
+  assert!(mesh.volume() > 0.0);
+}
+
+#[test]
+fn removes_duplicate_and_degenerate_triangles() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  let (first, second) = (mesh.triangles[0], mesh.triangles[5]);
+  mesh.triangles.push(first);
+  mesh
+    .triangles
+    .push(Triangle::new(second.a, second.c, second.b));
+  mesh
+    .triangles
+    .push(Triangle::new(first.a, first.a, first.b));
+  assert_eq!(mesh.triangles.len(), 15);
+  mesh.repair();
+  assert_eq!(mesh.triangles.len(), 12);
+  assert!(mesh.validate().is_valid());
+}
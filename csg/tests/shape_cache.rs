@@ -0,0 +1,53 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A shape evaluated with a cache gives the same mesh as one evaluated without, on the
+//! run that fills the cache and on the run that loads from it.
+
+use csg::{Cache, CsgOptions, Mesh, Pt3, Shape};
+
+fn corners(mesh: &Mesh) -> Vec<[Pt3; 3]> {
+  mesh.triangles.iter().map(|t| [t.a, t.b, t.c]).collect()
+}
+
+#[test]
+fn cached_results_match() {
+  let mut shape = Shape::mesh(Mesh::cube(10.0, 10.0, 10.0, true));
+  for i in 0..3 {
+    let mut hole = Shape::mesh(Mesh::cylinder(1.0, 1.0, 20.0, 16, true));
+    hole.translate(Pt3::new(-3.0 + 3.0 * i as f64, 0.0, 0.0));
+    shape -= hole;
+  }
+  let mut shape = shape + Shape::mesh(Mesh::sphere(4.0, 16));
+  shape.rotate_z(30.0);
+
+  let dir = std::env::temp_dir().join(format!("csg_shape_cache_{}", std::process::id()));
+  let cache = Cache::new(&dir);
+  let expected = corners(&shape.evaluate());
+  for _ in 0..2 {
+    let cached = shape.evaluate_cached(&CsgOptions::default(), &cache);
+    assert!(corners(&cached) == expected);
+  }
+  // one file for the difference and one for the union
+  assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+  std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks that transforms of meshes keep the normals pointing out.
+
+use csg::{Mesh, Mt4, Pt3, Triangle, VecTriangle};
+
+fn assert_outward(mesh: &Mesh, volume: f64) {
+  assert!(mesh.validate().is_valid());
+  assert!((mesh.volume() - volume).abs() < 1.0e-9 * volume);
+}
+
+#[test]
+fn mirror() {
+  let mut mesh = Mesh::cube(1.0, 2.0, 3.0, false);
+  mesh.mirror(Pt3::new(1.0, 1.0, 0.0), Pt3::new(5.0, 0.0, 0.0));
+  assert_outward(&mesh, 6.0);
+}
+
+#[test]
+fn negative_determinant() {
+  let mut mesh = Mesh::sphere(1.0, 16);
+  let volume = mesh.volume();
+  let matrix = Mt4::scale_matrix(-2.0, 1.0, 0.5);
+  assert!(matrix.determinant() < 0.0);
+  mesh.transform(&matrix);
+  assert_outward(&mesh, volume);
+}
+
+#[test]
+fn triangle_list() {
+  let mut triangles: Vec<Triangle> = Mesh::cube(1.0, 2.0, 3.0, false).triangles;
+  triangles.mirror(Pt3::new(0.0, 0.0, 1.0), Pt3::new(0.0, 0.0, 0.0));
+  assert_outward(&Mesh::from_triangles(triangles), 6.0);
+}
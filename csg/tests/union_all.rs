@@ -0,0 +1,115 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the booleans of many operands.
+
+mod common;
+
+use {
+  common::bits,
+  csg::{Mesh, Pt3, CSG},
+};
+
+/// A row of spheres that each overlap the next one.
+fn row() -> Vec<CSG> {
+  (0..6)
+    .map(|i| {
+      let mut sphere = Mesh::sphere(1.0, 16);
+      sphere.translate(Pt3::new(i as f64 * 1.5, 0.0, (i % 2) as f64 * 0.25));
+      CSG::from_mesh(sphere)
+    })
+    .collect()
+}
+
+/// Cubes on a grid with gaps between them.
+fn apart() -> Vec<CSG> {
+  (0..5)
+    .map(|i| {
+      let mut cube = Mesh::cube(1.0, 1.0, 1.0, false);
+      cube.translate(Pt3::new(i as f64 * 2.0, (i % 2) as f64 * 3.0, 0.0));
+      CSG::from_mesh(cube)
+    })
+    .collect()
+}
+
+fn assert_same_solid(a: CSG, b: CSG) {
+  let a = Mesh::from_csg(a);
+  let b = Mesh::from_csg(b);
+  assert!(a.validate().is_valid());
+  assert!(b.validate().is_valid());
+  assert!((a.volume() - b.volume()).abs() < 1.0e-9 * b.volume());
+  assert!((a.surface_area() - b.surface_area()).abs() < 1.0e-9 * b.surface_area());
+}
+
+#[test]
+fn balanced_union_matches_a_fold() {
+  let fold = row().into_iter().reduce(|a, b| a.union(b)).unwrap();
+  assert_same_solid(CSG::union_all(row()), fold);
+  // the spheres overlap, so the union is less than their sum
+  let sum: f64 = row().into_iter().map(|s| Mesh::from_csg(s).volume()).sum();
+  assert!(Mesh::from_csg(CSG::union_all(row())).volume() < 0.97 * sum);
+}
+
+#[test]
+fn subtract_all_matches_a_fold() {
+  // a block with the spheres hollowed out of it, the last one pokes out of the end
+  let base = || {
+    let mut cube = Mesh::cube(10.0, 3.0, 3.0, false);
+    cube.translate(Pt3::new(-2.0, -1.5, -1.5));
+    CSG::from_mesh(cube)
+  };
+  let fold = row().into_iter().fold(base(), |a, b| a.subtract(b));
+  assert_same_solid(CSG::subtract_all(base(), row()), fold);
+  let meshes = row().into_iter().map(Mesh::from_csg);
+  let mesh = Mesh::subtract_all(Mesh::from_csg(base()), meshes);
+  assert!(mesh.validate().is_valid());
+}
+
+#[test]
+fn disjoint_operands_are_put_together() {
+  // no boolean runs, so the polygons come through untouched and in order
+  let expected: Vec<Vec<u64>> = apart().iter().flat_map(bits).collect();
+  assert!(bits(&CSG::union_all(apart())) == expected);
+  let mesh = Mesh::union_all(apart().into_iter().map(Mesh::from_csg));
+  assert_eq!(mesh.triangles.len(), 5 * 12);
+
+  // tools that miss the base leave it as it was
+  let mut base = Mesh::cube(1.0, 1.0, 1.0, false);
+  base.translate(Pt3::new(0.0, 0.0, 5.0));
+  let base = CSG::from_mesh(base);
+  let expected = bits(&base);
+  assert!(bits(&CSG::subtract_all(base, apart())) == expected);
+}
+
+#[test]
+fn no_operands() {
+  assert!(CSG::union_all(Vec::new()).polygons.is_empty());
+  assert!(Mesh::union_all(Vec::new()).triangles.is_empty());
+  let base = CSG::from_mesh(Mesh::cube(1.0, 1.0, 1.0, false));
+  let expected = bits(&base);
+  assert!(bits(&CSG::subtract_all(base, Vec::new())) == expected);
+  let base = Mesh::cube(1.0, 1.0, 1.0, false);
+  assert_eq!(Mesh::subtract_all(base, Vec::new()).triangles.len(), 12);
+  // one operand is returned as it is
+  let expected = bits(&apart()[0]);
+  assert!(bits(&CSG::union_all(apart().into_iter().take(1))) == expected);
+}
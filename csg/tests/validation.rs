@@ -0,0 +1,117 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of Mesh::validate.
+
+use csg::{Mesh, Pt3, Triangle};
+
+#[test]
+fn closed_mesh_is_valid() {
+  let report = Mesh::sphere(10.0, 40).validate();
+  assert!(report.is_valid());
+  assert_eq!(report.shells, 1);
+}
+
+#[test]
+fn overlapping_cubes_intersect() {
+  let mut mesh = Mesh::cube(10.0, 10.0, 10.0, false);
+  let mut other = Mesh::cube(10.0, 10.0, 10.0, false);
+  other.translate(Pt3::new(3.0, 4.0, 5.0));
+  mesh.triangles.append(&mut other.triangles);
+  let report = mesh.validate();
+  assert!(!report.self_intersections.is_empty());
+  assert_eq!(report.shells, 2);
+}
+
+#[test]
+fn large_triangle_through_a_fine_mesh() {
+  let mut mesh = Mesh::sphere(1.0, 200);
+  mesh.triangles.push(Triangle::new(
+    Pt3::new(-1000.0, -1000.0, 0.1),
+    Pt3::new(1000.0, -1000.0, 0.1),
+    Pt3::new(0.0, 1000.0, 0.1),
+  ));
+  let big = mesh.triangles.len() - 1;
+  let report = mesh.validate();
+  assert!(!report.self_intersections.is_empty());
+  assert!(report
+    .self_intersections
+    .iter()
+    .all(|&(s, t)| s == big || t == big));
+}
+
+#[test]
+fn open_boundary() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  mesh.triangles.pop();
+  let report = mesh.validate();
+  assert!(!report.is_valid());
+  assert_eq!(report.open_edges.len(), 3);
+}
+
+#[test]
+fn non_manifold_edge() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  let t = mesh.triangles[0];
+  mesh
+    .triangles
+    .push(Triangle::new(t.b, t.a, Pt3::new(5.0, 5.0, 5.0)));
+  let report = mesh.validate();
+  assert!(!report.is_valid());
+  assert_eq!(report.non_manifold_edges.len(), 1);
+}
+
+#[test]
+fn non_manifold_vertex() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  let mut other = Mesh::cube(1.0, 1.0, 1.0, false);
+  other.translate(Pt3::new(1.0, 1.0, 1.0));
+  mesh.triangles.append(&mut other.triangles);
+  let report = mesh.validate();
+  assert!(!report.is_valid());
+  assert_eq!(report.non_manifold_vertices, vec![Pt3::new(1.0, 1.0, 1.0)]);
+  assert!(report.open_edges.is_empty());
+  assert!(report.non_manifold_edges.is_empty());
+}
+
+#[test]
+fn inconsistent_winding() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  let t = &mut mesh.triangles[0];
+  std::mem::swap(&mut t.b, &mut t.c);
+  let report = mesh.validate();
+  assert!(!report.is_valid());
+  assert_eq!(report.inconsistent_edges.len(), 3);
+}
+
+#[test]
+fn degenerate_triangle() {
+  let mut mesh = Mesh::cube(1.0, 1.0, 1.0, false);
+  mesh.triangles.push(Triangle::new(
+    Pt3::new(0.0, 0.0, 0.0),
+    Pt3::new(0.5, 0.0, 0.0),
+    Pt3::new(1.0, 0.0, 0.0),
+  ));
+  let report = mesh.validate();
+  assert!(!report.is_valid());
+  assert_eq!(report.degenerate_triangles, vec![12]);
+}
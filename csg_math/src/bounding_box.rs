@@ -0,0 +1,172 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Boxes around sets of points.
+
+use crate::pt3::Pt3;
+
+/// An axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+  pub min: Pt3,
+  pub max: Pt3,
+}
+
+impl std::fmt::Display for Aabb {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "[ {}, {} ]", self.min, self.max)
+  }
+}
+
+impl Aabb {
+  pub fn new(min: Pt3, max: Pt3) -> Self {
+    Self { min, max }
+  }
+
+  /// The smallest box around some points.
+  ///
+  /// return: The box or None if there are no points.
+  pub fn from_points(points: impl IntoIterator<Item = Pt3>) -> Option<Self> {
+    let mut points = points.into_iter();
+    let first = points.next()?;
+    let mut aabb = Self::new(first, first);
+    for p in points {
+      aabb.min = Pt3::new(
+        aabb.min.x.min(p.x),
+        aabb.min.y.min(p.y),
+        aabb.min.z.min(p.z),
+      );
+      aabb.max = Pt3::new(
+        aabb.max.x.max(p.x),
+        aabb.max.y.max(p.y),
+        aabb.max.z.max(p.z),
+      );
+    }
+    Some(aabb)
+  }
+
+  /// The size of the box along each axis.
+  pub fn size(&self) -> Pt3 {
+    self.max - self.min
+  }
+
+  /// The point in the middle of the box.
+  pub fn center(&self) -> Pt3 {
+    (self.min + self.max) / 2.0
+  }
+
+  /// The smallest box around both boxes.
+  pub fn union(&self, other: &Aabb) -> Aabb {
+    Self::new(
+      Pt3::new(
+        self.min.x.min(other.min.x),
+        self.min.y.min(other.min.y),
+        self.min.z.min(other.min.z),
+      ),
+      Pt3::new(
+        self.max.x.max(other.max.x),
+        self.max.y.max(other.max.y),
+        self.max.z.max(other.max.z),
+      ),
+    )
+  }
+
+  /// The box where two boxes overlap.
+  ///
+  /// return: The overlap or None if the boxes are disjoint. Boxes that touch overlap
+  /// in a flat box.
+  pub fn intersection(&self, other: &Aabb) -> Option<Aabb> {
+    let min = Pt3::new(
+      self.min.x.max(other.min.x),
+      self.min.y.max(other.min.y),
+      self.min.z.max(other.min.z),
+    );
+    let max = Pt3::new(
+      self.max.x.min(other.max.x),
+      self.max.y.min(other.max.y),
+      self.max.z.min(other.max.z),
+    );
+    if min.x > max.x || min.y > max.y || min.z > max.z {
+      None
+    } else {
+      Some(Self::new(min, max))
+    }
+  }
+
+  /// Whether a point is inside or on the box.
+  pub fn contains(&self, point: Pt3) -> bool {
+    point.x >= self.min.x
+      && point.y >= self.min.y
+      && point.z >= self.min.z
+      && point.x <= self.max.x
+      && point.y <= self.max.y
+      && point.z <= self.max.z
+  }
+
+  /// Whether another box is inside or on this box.
+  pub fn contains_box(&self, other: &Aabb) -> bool {
+    self.contains(other.min) && self.contains(other.max)
+  }
+
+  /// The box grown by margin on every side.
+  pub fn expanded(&self, margin: f64) -> Aabb {
+    let margin = Pt3::new(margin, margin, margin);
+    Self::new(self.min - margin, self.max + margin)
+  }
+}
+
+/// An oriented bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+  /// The point in the middle of the box.
+  pub center: Pt3,
+  /// The directions of the edges of the box, unit length, perpendicular to each
+  /// other and right handed.
+  pub axes: [Pt3; 3],
+  /// Half the size of the box along each of the axes.
+  pub half_size: Pt3,
+}
+
+impl Obb {
+  /// The volume of the box.
+  pub fn volume(&self) -> f64 {
+    8.0 * self.half_size.x * self.half_size.y * self.half_size.z
+  }
+
+  /// The eight corners of the box.
+  pub fn corners(&self) -> [Pt3; 8] {
+    let mut corners = [self.center; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+      for k in 0..3 {
+        let sign = if i & (1 << k) == 0 { -1.0 } else { 1.0 };
+        *corner += self.axes[k] * (sign * self.half_size[k]);
+      }
+    }
+    corners
+  }
+
+  /// Whether a point is inside or on the box.
+  pub fn contains(&self, point: Pt3) -> bool {
+    let d = point - self.center;
+    (0..3).all(|k| d.dot(self.axes[k]).abs() <= self.half_size[k])
+  }
+}
@@ -22,17 +22,23 @@
 
 //! A double precision, non generic, math library.
 
+mod bounding_box;
 mod mt4;
+mod predicates;
 mod pt2;
 mod pt3;
 mod pt4;
+mod quat;
 mod rng;
 
 pub use {
+  bounding_box::{Aabb, Obb},
   mt4::Mt4,
+  predicates::{orient3d, orient3d_exact},
   pt2::{CubicBezier2D, CubicBezierChain2D, Pt2, QuadraticBezier2D, VecPt2},
   pt3::{CubicBezier3D, CubicBezierChain3D, Pt3, QuadraticBezier3D, VecPt3},
   pt4::Pt4,
+  quat::Quat,
   rng::MersenneTwister,
 };
 
@@ -136,7 +136,7 @@ impl Mt4 {
         0.0,
       ),
       Pt4::new(
-        z * x * (1.0 - c) - z * s,
+        z * x * (1.0 - c) - y * s,
         z * y * (1.0 - c) + x * s,
         c + z * z * (1.0 - c),
         0.0,
@@ -146,6 +146,77 @@ impl Mt4 {
     .transposed()
   }
 
+  /// A rotation around an arbitrary axis through a pivot point, counter clockwise when
+  /// looking down the axis towards the pivot.
+  ///
+  /// axis: The direction of the axis, does not need to be normalized.
+  ///
+  /// pivot: A point on the axis.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: The matrix.
+  pub fn rot_axis_matrix(axis: Pt3, pivot: Pt3, degrees: f64) -> Self {
+    assert!(axis.len2() > 0.0);
+    let a = axis.normalized();
+    Mt4::translate_matrix(pivot.x, pivot.y, pivot.z)
+      * Mt4::rot_vec(a.x, a.y, a.z, degrees)
+      * Mt4::translate_matrix(-pivot.x, -pivot.y, -pivot.z)
+  }
+
+  /// A reflection across a plane.
+  ///
+  /// normal: The normal of the plane, does not need to be normalized.
+  ///
+  /// point: A point on the plane.
+  ///
+  /// return: The matrix.
+  pub fn mirror_matrix(normal: Pt3, point: Pt3) -> Self {
+    assert!(normal.len2() > 0.0);
+    let n = normal.normalized();
+    let d = 2.0 * n.dot(point);
+    Mt4::new(
+      Pt4::new(
+        1.0 - 2.0 * n.x * n.x,
+        -2.0 * n.x * n.y,
+        -2.0 * n.x * n.z,
+        0.0,
+      ),
+      Pt4::new(
+        -2.0 * n.y * n.x,
+        1.0 - 2.0 * n.y * n.y,
+        -2.0 * n.y * n.z,
+        0.0,
+      ),
+      Pt4::new(
+        -2.0 * n.z * n.x,
+        -2.0 * n.z * n.y,
+        1.0 - 2.0 * n.z * n.z,
+        0.0,
+      ),
+      Pt4::new(d * n.x, d * n.y, d * n.z, 1.0),
+    )
+  }
+
+  /// The determinant, negative when the matrix turns space inside out like a mirror does.
+  pub fn determinant(&self) -> f64 {
+    // expand along pairs of columns using the 2x2 minors of the first two
+    let m = |c: usize, r: usize| self[c * 4 + r];
+    let s0 = m(0, 0) * m(1, 1) - m(1, 0) * m(0, 1);
+    let s1 = m(0, 0) * m(1, 2) - m(1, 0) * m(0, 2);
+    let s2 = m(0, 0) * m(1, 3) - m(1, 0) * m(0, 3);
+    let s3 = m(0, 1) * m(1, 2) - m(1, 1) * m(0, 2);
+    let s4 = m(0, 1) * m(1, 3) - m(1, 1) * m(0, 3);
+    let s5 = m(0, 2) * m(1, 3) - m(1, 2) * m(0, 3);
+    let c5 = m(2, 2) * m(3, 3) - m(3, 2) * m(2, 3);
+    let c4 = m(2, 1) * m(3, 3) - m(3, 1) * m(2, 3);
+    let c3 = m(2, 1) * m(3, 2) - m(3, 1) * m(2, 2);
+    let c2 = m(2, 0) * m(3, 3) - m(3, 0) * m(2, 3);
+    let c1 = m(2, 0) * m(3, 2) - m(3, 0) * m(2, 2);
+    let c0 = m(2, 0) * m(3, 1) - m(3, 0) * m(2, 1);
+    s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0
+  }
+
   pub fn perspective_matrix(fovy: f64, aspect: f64, near: f64, far: f64) -> Self {
     let tan_half_fovy = dtan(fovy / 2.0);
     Mt4::new(
@@ -268,7 +339,7 @@ impl Mt4 {
     } else {
       det = 1.0 / det;
       for i in 0..16 {
-        out[i] = out[i] * det;
+        out[i] *= det;
       }
       Some(out)
     }
@@ -332,12 +403,22 @@ impl Mt4 {
   }
 }
 
+/// The dot product of all four components, Pt4::dot leaves out w.
+fn dot4(a: Pt4, b: Pt4) -> f64 {
+  a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
+}
+
 impl std::ops::Mul<Pt4> for Mt4 {
   type Output = Pt4;
 
   fn mul(self, rhs: Pt4) -> Self::Output {
     let t = self.transposed();
-    Pt4::new(t.x.dot(rhs), t.y.dot(rhs), t.z.dot(rhs), t.w.dot(rhs))
+    Pt4::new(
+      dot4(t.x, rhs),
+      dot4(t.y, rhs),
+      dot4(t.z, rhs),
+      dot4(t.w, rhs),
+    )
   }
 }
 
@@ -361,28 +442,28 @@ impl std::ops::Mul<Mt4> for Mt4 {
     let t = self.transposed();
     Mt4::new(
       Pt4::new(
-        t.x.dot(rhs.x),
-        t.y.dot(rhs.x),
-        t.z.dot(rhs.x),
-        t.w.dot(rhs.x),
+        dot4(t.x, rhs.x),
+        dot4(t.y, rhs.x),
+        dot4(t.z, rhs.x),
+        dot4(t.w, rhs.x),
       ),
       Pt4::new(
-        t.x.dot(rhs.y),
-        t.y.dot(rhs.y),
-        t.z.dot(rhs.y),
-        t.w.dot(rhs.y),
+        dot4(t.x, rhs.y),
+        dot4(t.y, rhs.y),
+        dot4(t.z, rhs.y),
+        dot4(t.w, rhs.y),
       ),
       Pt4::new(
-        t.x.dot(rhs.z),
-        t.y.dot(rhs.z),
-        t.z.dot(rhs.z),
-        t.w.dot(rhs.z),
+        dot4(t.x, rhs.z),
+        dot4(t.y, rhs.z),
+        dot4(t.z, rhs.z),
+        dot4(t.w, rhs.z),
       ),
       Pt4::new(
-        t.x.dot(rhs.w),
-        t.y.dot(rhs.w),
-        t.z.dot(rhs.w),
-        t.w.dot(rhs.w),
+        dot4(t.x, rhs.w),
+        dot4(t.y, rhs.w),
+        dot4(t.z, rhs.w),
+        dot4(t.w, rhs.w),
       ),
     )
   }
@@ -0,0 +1,239 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Robust geometric predicates after Jonathan Shewchuk's "Adaptive Precision
+//! Floating-Point Arithmetic and Fast Robust Geometric Predicates".
+//!
+//! The predicates first evaluate the determinant in plain floating point and only
+//! fall back to exact expansion arithmetic when the result is too close to zero
+//! for its sign to be trusted.
+
+use crate::Pt3;
+
+/// Half an ulp of 1.0, the relative error of a single rounding.
+const EPSILON: f64 = f64::EPSILON * 0.5;
+
+/// Error bound for the floating point evaluation of orient3d.
+const O3D_ERRBOUND_A: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+
+/// The orientation of point d relative to the plane through a, b and c.
+///
+/// return: A positive value if d lies below the plane, a negative value if it lies
+/// above it and zero if the four points are coplanar. "Above" is the side from which
+/// a, b and c appear in counterclockwise order, so a negative result means d is in
+/// front of the plane for the right hand normal (b - a) x (c - a). The sign is
+/// always exact, the magnitude is approximately six times the volume of the
+/// tetrahedron.
+pub fn orient3d(a: Pt3, b: Pt3, c: Pt3, d: Pt3) -> f64 {
+  let adx = a.x - d.x;
+  let bdx = b.x - d.x;
+  let cdx = c.x - d.x;
+  let ady = a.y - d.y;
+  let bdy = b.y - d.y;
+  let cdy = c.y - d.y;
+  let adz = a.z - d.z;
+  let bdz = b.z - d.z;
+  let cdz = c.z - d.z;
+
+  let bdxcdy = bdx * cdy;
+  let cdxbdy = cdx * bdy;
+  let cdxady = cdx * ady;
+  let adxcdy = adx * cdy;
+  let adxbdy = adx * bdy;
+  let bdxady = bdx * ady;
+
+  let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+  let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+    + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+    + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+  let errbound = O3D_ERRBOUND_A * permanent;
+  if det > errbound || -det > errbound {
+    return det;
+  }
+  orient3d_exact(a, b, c, d)
+}
+
+/// orient3d evaluated with exact arithmetic only.
+///
+/// The determinant is expanded along the homogeneous column of the 4x4 matrix so
+/// that it becomes a sum of products of the input coordinates, which are computed
+/// without any rounding error.
+pub fn orient3d_exact(a: Pt3, b: Pt3, c: Pt3, d: Pt3) -> f64 {
+  let mut sum = Vec::with_capacity(96);
+  add_det3(&mut sum, b, c, d, -1.0);
+  add_det3(&mut sum, a, c, d, 1.0);
+  add_det3(&mut sum, a, b, d, -1.0);
+  add_det3(&mut sum, a, b, c, 1.0);
+  sum.iter().sum()
+}
+
+/// Add sign * det[p; q; r] to the expansion.
+fn add_det3(e: &mut Vec<f64>, p: Pt3, q: Pt3, r: Pt3, sign: f64) {
+  add_product(e, p.x, q.y, r.z, sign);
+  add_product(e, p.x, q.z, r.y, -sign);
+  add_product(e, p.y, q.x, r.z, -sign);
+  add_product(e, p.y, q.z, r.x, sign);
+  add_product(e, p.z, q.x, r.y, sign);
+  add_product(e, p.z, q.y, r.x, -sign);
+}
+
+/// Add sign * x * y * z to the expansion without rounding.
+fn add_product(e: &mut Vec<f64>, x: f64, y: f64, z: f64, sign: f64) {
+  let (hi, lo) = two_product(x, y);
+  let (a, b) = two_product(hi, z);
+  let (c, d) = two_product(lo, z);
+  for term in [b, a, d, c] {
+    grow_expansion(e, term * sign);
+  }
+}
+
+/// The sum a + b as a rounded value and the rounding error.
+#[inline(always)]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+  let x = a + b;
+  let b_virtual = x - a;
+  let a_virtual = x - b_virtual;
+  let b_roundoff = b - b_virtual;
+  let a_roundoff = a - a_virtual;
+  (x, a_roundoff + b_roundoff)
+}
+
+/// The product a * b as a rounded value and the rounding error.
+#[inline(always)]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+  let x = a * b;
+  (x, a.mul_add(b, -x))
+}
+
+/// Add a single value to a nonoverlapping expansion stored in increasing order of
+/// magnitude, dropping zero components.
+fn grow_expansion(e: &mut Vec<f64>, b: f64) {
+  let mut q = b;
+  let mut n = 0;
+  for i in 0..e.len() {
+    let (sum, err) = two_sum(q, e[i]);
+    q = sum;
+    if err != 0.0 {
+      e[n] = err;
+      n += 1;
+    }
+  }
+  e.truncate(n);
+  if q != 0.0 {
+    e.push(q);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::MersenneTwister};
+
+  /// The determinant in plain floating point, what orient3d starts with.
+  fn naive(a: Pt3, b: Pt3, c: Pt3, d: Pt3) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+    ad.z * (bd.x * cd.y - cd.x * bd.y)
+      + bd.z * (cd.x * ad.y - ad.x * cd.y)
+      + cd.z * (ad.x * bd.y - bd.x * ad.y)
+  }
+
+  /// The determinant of integer points, exact as long as it fits.
+  fn integer(a: [i128; 3], b: [i128; 3], c: [i128; 3], d: [i128; 3]) -> i128 {
+    let sub = |p: [i128; 3]| [p[0] - d[0], p[1] - d[1], p[2] - d[2]];
+    let (ad, bd, cd) = (sub(a), sub(b), sub(c));
+    ad[2] * (bd[0] * cd[1] - cd[0] * bd[1])
+      + bd[2] * (cd[0] * ad[1] - ad[0] * cd[1])
+      + cd[2] * (ad[0] * bd[1] - bd[0] * ad[1])
+  }
+
+  fn pt3(p: [i128; 3]) -> Pt3 {
+    Pt3::new(p[0] as f64, p[1] as f64, p[2] as f64)
+  }
+
+  #[test]
+  fn sign() {
+    let a = Pt3::new(0.0, 0.0, 0.0);
+    let b = Pt3::new(1.0, 0.0, 0.0);
+    let c = Pt3::new(0.0, 1.0, 0.0);
+    // above the ccw triangle is negative
+    assert_eq!(orient3d(a, b, c, Pt3::new(0.0, 0.0, 1.0)), -1.0);
+    assert_eq!(orient3d(a, b, c, Pt3::new(0.2, 0.3, -2.0)), 2.0);
+    assert_eq!(orient3d(b, a, c, Pt3::new(0.0, 0.0, 1.0)), 1.0);
+  }
+
+  #[test]
+  fn near_degenerate() {
+    // a small triangle and a point far away in its plane, moved off the plane by one
+    // unit. The determinant is a small integer that the naive evaluation loses in the
+    // rounding of products near 2^117.
+    let mut rng = MersenneTwister::with_seed(5489);
+    let mut random = |max: u32| (rng.raw() % max) as i128;
+    let mut wrong = 0;
+    for _ in 0..1000 {
+      let a = [random(1024), random(1024), random(1024)];
+      let b = [random(1024), random(1024), random(1024)];
+      let c = [random(1024), random(1024), random(1024)];
+      let (u, v) = (random(1 << 28), random(1 << 28));
+      let on_plane = [0, 1, 2].map(|i| a[i] + u * (b[i] - a[i]) + v * (c[i] - a[i]));
+      for offset in [-1, 1] {
+        let mut d = on_plane;
+        d[2] += offset;
+        let expected = integer(a, b, c, d).signum() as f64;
+        let (a, b, c, d) = (pt3(a), pt3(b), pt3(c), pt3(d));
+        assert_eq!(orient3d(a, b, c, d).signum(), expected);
+        assert_eq!(orient3d_exact(a, b, c, d).signum(), expected);
+        if naive(a, b, c, d).signum() != expected {
+          wrong += 1;
+        }
+      }
+    }
+    // the cases are hard enough to fool the naive determinant
+    assert!(wrong > 100, "the naive determinant got {wrong} signs wrong");
+  }
+
+  #[test]
+  fn coplanar_is_exactly_zero() {
+    let mut rng = MersenneTwister::with_seed(7);
+    let mut random = |max: u32| (rng.raw() % max) as i128;
+    for _ in 0..1000 {
+      let a = [random(1024), random(1024), random(1024)];
+      let b = [random(1024), random(1024), random(1024)];
+      let c = [random(1024), random(1024), random(1024)];
+      let (u, v) = (random(1 << 28), random(1 << 28));
+      let d = [0, 1, 2].map(|i| a[i] + u * (b[i] - a[i]) + v * (c[i] - a[i]));
+      assert_eq!(integer(a, b, c, d), 0);
+      let (a, b, c, d) = (pt3(a), pt3(b), pt3(c), pt3(d));
+      assert_eq!(orient3d(a, b, c, d), 0.0);
+      assert_eq!(orient3d(b, c, a, d), 0.0);
+      assert_eq!(orient3d(d, a, b, c), 0.0);
+    }
+    // points that are not integers, on the plane x + y + z = 1 with halves and quarters
+    let a = Pt3::new(0.25, 0.5, 0.25);
+    let b = Pt3::new(1.5, -0.75, 0.25);
+    let c = Pt3::new(-2.0, 1.0, 2.0);
+    let d = Pt3::new(1.0e6 + 0.5, -1.0e6, 0.5);
+    assert_eq!(orient3d(a, b, c, d), 0.0);
+    // the normal of a, b and c points towards -(1, 1, 1)
+    assert!(orient3d(a, b, c, Pt3::new(d.x, d.y, 0.5 + f64::EPSILON)) > 0.0);
+    assert!(orient3d(a, b, c, Pt3::new(d.x, d.y, 0.5 - f64::EPSILON)) < 0.0);
+  }
+}
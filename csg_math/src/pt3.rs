@@ -23,6 +23,7 @@
 
 use crate::dcos;
 use crate::dsin;
+use crate::mt4::Mt4;
 use crate::pt4::Pt4;
 use crate::rng::MersenneTwister;
 
@@ -31,6 +32,38 @@ pub trait VecPt3 {
   fn rotate_x(&mut self, degrees: f64) -> &mut Self;
   fn rotate_y(&mut self, degrees: f64) -> &mut Self;
   fn rotate_z(&mut self, degrees: f64) -> &mut Self;
+  // the transforms have default bodies so implementors written before they existed
+  // still compile, they work on anything that is a slice of Pt3s
+  fn transform(&mut self, matrix: &Mt4) -> &mut Self
+  where
+    Self: AsMut<[Pt3]>,
+  {
+    for p in self.as_mut() {
+      p.transform(matrix);
+    }
+    self
+  }
+
+  fn scale(&mut self, factors: Pt3) -> &mut Self
+  where
+    Self: AsMut<[Pt3]>,
+  {
+    self.transform(&Mt4::scale_matrix(factors.x, factors.y, factors.z))
+  }
+
+  fn mirror(&mut self, normal: Pt3, point: Pt3) -> &mut Self
+  where
+    Self: AsMut<[Pt3]>,
+  {
+    self.transform(&Mt4::mirror_matrix(normal, point))
+  }
+
+  fn rotate_about(&mut self, axis: Pt3, pivot: Pt3, degrees: f64) -> &mut Self
+  where
+    Self: AsMut<[Pt3]>,
+  {
+    self.transform(&Mt4::rot_axis_matrix(axis, pivot, degrees))
+  }
 }
 
 impl VecPt3 for Vec<Pt3> {
@@ -228,6 +261,15 @@ impl Pt3 {
     *self = self.rotated_z(degrees);
   }
 
+  /// The point moved by an affine matrix, translation included.
+  pub fn transformed(self, matrix: &Mt4) -> Self {
+    (*matrix * self.as_pt4(1.0)).as_pt3()
+  }
+
+  pub fn transform(&mut self, matrix: &Mt4) {
+    *self = self.transformed(matrix);
+  }
+
   pub fn lerp(self, b: Self, t: f64) -> Self {
     self + (b - self) * t
   }
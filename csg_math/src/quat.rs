@@ -0,0 +1,314 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Quaternions for rotations that can be chained and interpolated without the drift
+//! and gimbal lock of multiplying rotation matrices.
+
+use crate::dcos;
+use crate::dsin;
+use crate::mt4::Mt4;
+use crate::pt3::Pt3;
+use crate::pt4::Pt4;
+
+/// A rotation stored as a unit quaternion, x, y and z are the vector part and w the
+/// scalar part.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+  pub x: f64,
+  pub y: f64,
+  pub z: f64,
+  pub w: f64,
+}
+
+impl Default for Quat {
+  fn default() -> Self {
+    Self::identity()
+  }
+}
+
+impl std::fmt::Display for Quat {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "[ {}, {}, {}, {} ]", self.x, self.y, self.z, self.w)
+  }
+}
+
+impl std::ops::Mul for Quat {
+  type Output = Self;
+
+  /// The rotation rhs followed by the rotation self.
+  fn mul(self, rhs: Self) -> Self::Output {
+    Self::new(
+      self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+      self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+      self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+      self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+    )
+  }
+}
+
+impl std::ops::MulAssign for Quat {
+  fn mul_assign(&mut self, rhs: Self) {
+    *self = *self * rhs;
+  }
+}
+
+impl std::ops::Mul<Pt3> for Quat {
+  type Output = Pt3;
+
+  fn mul(self, rhs: Pt3) -> Self::Output {
+    self.rotate(rhs)
+  }
+}
+
+impl std::ops::Neg for Quat {
+  type Output = Self;
+
+  /// The same rotation, q and -q turn points the same way.
+  fn neg(self) -> Self::Output {
+    Self::new(-self.x, -self.y, -self.z, -self.w)
+  }
+}
+
+impl From<Quat> for Mt4 {
+  fn from(q: Quat) -> Self {
+    q.to_mt4()
+  }
+}
+
+impl From<Mt4> for Quat {
+  fn from(m: Mt4) -> Self {
+    Quat::from_mt4(&m)
+  }
+}
+
+impl Quat {
+  pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+    Self { x, y, z, w }
+  }
+
+  /// The rotation that leaves everything where it is.
+  pub fn identity() -> Self {
+    Self::new(0.0, 0.0, 0.0, 1.0)
+  }
+
+  /// A rotation around an axis through the origin, counter clockwise when looking down
+  /// the axis towards the origin, the same way as Mt4::rot_vec.
+  ///
+  /// axis: The direction of the axis, does not need to be normalized.
+  ///
+  /// degrees: The degrees of rotation.
+  ///
+  /// return: The quaternion.
+  pub fn from_axis_angle(axis: Pt3, degrees: f64) -> Self {
+    assert!(axis.len2() > 0.0);
+    let a = axis.normalized() * dsin(degrees / 2.0);
+    Self::new(a.x, a.y, a.z, dcos(degrees / 2.0))
+  }
+
+  /// Rotations around the fixed X, then Y, then Z axes, the same as
+  /// rot_z_matrix(z) * rot_y_matrix(y) * rot_x_matrix(x).
+  ///
+  /// x: The degrees of rotation around the X axis.
+  ///
+  /// y: The degrees of rotation around the Y axis.
+  ///
+  /// z: The degrees of rotation around the Z axis.
+  ///
+  /// return: The quaternion.
+  pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+    Self::from_axis_angle(Pt3::new(0.0, 0.0, 1.0), z)
+      * Self::from_axis_angle(Pt3::new(0.0, 1.0, 0.0), y)
+      * Self::from_axis_angle(Pt3::new(1.0, 0.0, 0.0), x)
+  }
+
+  /// The smallest rotation that turns one direction into another. Opposite directions
+  /// are turned half way around an arbitrary perpendicular axis.
+  ///
+  /// from: The start direction, does not need to be normalized.
+  ///
+  /// to: The end direction, does not need to be normalized.
+  ///
+  /// return: The quaternion.
+  pub fn from_two_vectors(from: Pt3, to: Pt3) -> Self {
+    assert!(from.len2() > 0.0 && to.len2() > 0.0);
+    let a = from.normalized();
+    let b = to.normalized();
+    let d = a.dot(b);
+    if d < -1.0 + 1.0e-12 {
+      let helper = if a.x.abs() < 0.9 {
+        Pt3::new(1.0, 0.0, 0.0)
+      } else {
+        Pt3::new(0.0, 1.0, 0.0)
+      };
+      return Self::from_axis_angle(a.cross(helper), 180.0);
+    }
+    // the half way rotation, (1 + d, a x b) normalized has half the angle
+    let c = a.cross(b);
+    Self::new(c.x, c.y, c.z, 1.0 + d).normalized()
+  }
+
+  /// The rotation part of a matrix. The matrix should be a rotation, possibly with a
+  /// translation, scale and shear give a meaningless result.
+  ///
+  /// m: The matrix.
+  ///
+  /// return: The quaternion.
+  pub fn from_mt4(m: &Mt4) -> Self {
+    // element at row r and column c, the matrix stores columns
+    let e = |r: usize, c: usize| m[c * 4 + r];
+    let trace = e(0, 0) + e(1, 1) + e(2, 2);
+    // start from the largest of w, x, y and z so the division is well conditioned
+    let q = if trace > 0.0 {
+      let s = (trace + 1.0).sqrt() * 2.0;
+      Self::new(
+        (e(2, 1) - e(1, 2)) / s,
+        (e(0, 2) - e(2, 0)) / s,
+        (e(1, 0) - e(0, 1)) / s,
+        s / 4.0,
+      )
+    } else if e(0, 0) > e(1, 1) && e(0, 0) > e(2, 2) {
+      let s = (1.0 + e(0, 0) - e(1, 1) - e(2, 2)).sqrt() * 2.0;
+      Self::new(
+        s / 4.0,
+        (e(0, 1) + e(1, 0)) / s,
+        (e(0, 2) + e(2, 0)) / s,
+        (e(2, 1) - e(1, 2)) / s,
+      )
+    } else if e(1, 1) > e(2, 2) {
+      let s = (1.0 + e(1, 1) - e(0, 0) - e(2, 2)).sqrt() * 2.0;
+      Self::new(
+        (e(0, 1) + e(1, 0)) / s,
+        s / 4.0,
+        (e(1, 2) + e(2, 1)) / s,
+        (e(0, 2) - e(2, 0)) / s,
+      )
+    } else {
+      let s = (1.0 + e(2, 2) - e(0, 0) - e(1, 1)).sqrt() * 2.0;
+      Self::new(
+        (e(0, 2) + e(2, 0)) / s,
+        (e(1, 2) + e(2, 1)) / s,
+        s / 4.0,
+        (e(1, 0) - e(0, 1)) / s,
+      )
+    };
+    q.normalized()
+  }
+
+  /// The rotation as a matrix.
+  pub fn to_mt4(&self) -> Mt4 {
+    let Self { x, y, z, w } = *self;
+    Mt4::new(
+      Pt4::new(
+        1.0 - 2.0 * (y * y + z * z),
+        2.0 * (x * y + z * w),
+        2.0 * (x * z - y * w),
+        0.0,
+      ),
+      Pt4::new(
+        2.0 * (x * y - z * w),
+        1.0 - 2.0 * (x * x + z * z),
+        2.0 * (y * z + x * w),
+        0.0,
+      ),
+      Pt4::new(
+        2.0 * (x * z + y * w),
+        2.0 * (y * z - x * w),
+        1.0 - 2.0 * (x * x + y * y),
+        0.0,
+      ),
+      Pt4::new(0.0, 0.0, 0.0, 1.0),
+    )
+  }
+
+  /// The axis and degrees of the rotation, the angle is in 0..=180 with the axis
+  /// flipped as needed. The identity gives the X axis.
+  pub fn to_axis_angle(&self) -> (Pt3, f64) {
+    let q = if self.w < 0.0 { -*self } else { *self };
+    let v = Pt3::new(q.x, q.y, q.z);
+    let s = v.len();
+    if s == 0.0 {
+      return (Pt3::new(1.0, 0.0, 0.0), 0.0);
+    }
+    (v / s, 2.0 * s.atan2(q.w).to_degrees())
+  }
+
+  /// Turn a point around the origin.
+  pub fn rotate(&self, p: Pt3) -> Pt3 {
+    // p + 2w(v x p) + 2v x (v x p) for a unit quaternion
+    let v = Pt3::new(self.x, self.y, self.z);
+    let t = v.cross(p) * 2.0;
+    p + t * self.w + v.cross(t)
+  }
+
+  pub fn dot(&self, rhs: Self) -> f64 {
+    self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+  }
+
+  pub fn len(&self) -> f64 {
+    self.dot(*self).sqrt()
+  }
+
+  pub fn normalized(&self) -> Self {
+    let l = self.len();
+    Self::new(self.x / l, self.y / l, self.z / l, self.w / l)
+  }
+
+  /// The opposite rotation, for a unit quaternion this is the inverse.
+  pub fn conjugate(&self) -> Self {
+    Self::new(-self.x, -self.y, -self.z, self.w)
+  }
+
+  /// Spherical linear interpolation, turns at a constant rate along the shortest way
+  /// from self to b.
+  ///
+  /// b: The end rotation.
+  ///
+  /// t: The fraction of the way from self to b, 0 gives self and 1 gives b.
+  ///
+  /// return: The quaternion.
+  pub fn slerp(&self, b: Self, t: f64) -> Self {
+    let mut d = self.dot(b);
+    // q and -q are the same rotation, go the short way around
+    let b = if d < 0.0 {
+      d = -d;
+      -b
+    } else {
+      b
+    };
+    let (s0, s1) = if d > 1.0 - 1.0e-9 {
+      // nearly the same, sin of the angle is too small to divide by
+      (1.0 - t, t)
+    } else {
+      let theta = d.acos();
+      let sin = theta.sin();
+      (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    Self::new(
+      self.x * s0 + b.x * s1,
+      self.y * s0 + b.y * s1,
+      self.z * s0 + b.z * s1,
+      self.w * s0 + b.w * s1,
+    )
+    .normalized()
+  }
+}
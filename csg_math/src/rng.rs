@@ -38,6 +38,12 @@ pub struct MersenneTwister {
   index: usize,
 }
 
+impl Default for MersenneTwister {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl MersenneTwister {
   fn next(&mut self) -> u32 {
     let mut y: u32;
@@ -88,7 +94,7 @@ impl MersenneTwister {
   }
 
   pub fn f32_minmax(&mut self, min: f32, max: f32) -> f32 {
-    min + (max - min) * self.f32_0_1() as f32
+    min + (max - min) * self.f32_0_1()
   }
 
   pub fn f64_minmax(&mut self, min: f64, max: f64) -> f64 {
@@ -109,7 +115,7 @@ impl MersenneTwister {
     unsafe {
       result.buffer.set_len(STATE_VECTOR_LENGTH);
     }
-    result.buffer[0] = seed & 0xffffffff;
+    result.buffer[0] = seed;
     while result.index < STATE_VECTOR_LENGTH {
       result.buffer[result.index] =
         ((6069 * result.buffer[result.index - 1] as usize) & 0xffffffff) as u32;
@@ -0,0 +1,97 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the Aabb operations.
+
+use csg_math::{Aabb, Pt3};
+
+fn aabb(min: [f64; 3], max: [f64; 3]) -> Aabb {
+  Aabb::new(
+    Pt3::new(min[0], min[1], min[2]),
+    Pt3::new(max[0], max[1], max[2]),
+  )
+}
+
+#[test]
+fn union() {
+  let a = aabb([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]);
+  let b = aabb([-1.0, 1.0, 2.0], [0.5, 4.0, 5.0]);
+  assert_eq!(a.union(&b), aabb([-1.0, 0.0, 0.0], [1.0, 4.0, 5.0]));
+  assert_eq!(a.union(&b), b.union(&a));
+  // disjoint boxes give the box around both
+  let c = aabb([10.0, 10.0, 10.0], [11.0, 11.0, 11.0]);
+  assert_eq!(a.union(&c), aabb([0.0, 0.0, 0.0], [11.0, 11.0, 11.0]));
+  // a box inside another changes nothing
+  let d = aabb([0.25, 0.5, 1.0], [0.75, 1.5, 2.0]);
+  assert_eq!(a.union(&d), a);
+}
+
+#[test]
+fn intersection() {
+  let a = aabb([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]);
+  let b = aabb([-1.0, 1.0, 2.0], [0.5, 4.0, 5.0]);
+  assert_eq!(
+    a.intersection(&b),
+    Some(aabb([0.0, 1.0, 2.0], [0.5, 2.0, 3.0]))
+  );
+  assert_eq!(a.intersection(&b), b.intersection(&a));
+  // touching boxes overlap in a flat box
+  let c = aabb([1.0, 0.0, 0.0], [2.0, 1.0, 1.0]);
+  assert_eq!(
+    a.intersection(&c),
+    Some(aabb([1.0, 0.0, 0.0], [1.0, 1.0, 1.0]))
+  );
+  // apart along any one axis is disjoint
+  assert_eq!(
+    a.intersection(&aabb([1.5, 0.0, 0.0], [2.0, 1.0, 1.0])),
+    None
+  );
+  assert_eq!(
+    a.intersection(&aabb([0.0, -3.0, 0.0], [1.0, -1.0, 1.0])),
+    None
+  );
+  assert_eq!(
+    a.intersection(&aabb([0.0, 0.0, 3.5], [1.0, 1.0, 4.0])),
+    None
+  );
+  // a box inside another is the overlap
+  let d = aabb([0.25, 0.5, 1.0], [0.75, 1.5, 2.0]);
+  assert_eq!(a.intersection(&d), Some(d));
+}
+
+#[test]
+fn contains() {
+  let a = aabb([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]);
+  assert!(a.contains(Pt3::new(0.5, 1.0, 1.5)));
+  // the faces, edges and corners are inside
+  assert!(a.contains(Pt3::new(1.0, 1.0, 1.5)));
+  assert!(a.contains(Pt3::new(0.0, 2.0, 1.5)));
+  assert!(a.contains(Pt3::new(0.0, 0.0, 0.0)));
+  assert!(a.contains(Pt3::new(1.0, 2.0, 3.0)));
+  // outside along any one axis is outside
+  assert!(!a.contains(Pt3::new(-0.1, 1.0, 1.5)));
+  assert!(!a.contains(Pt3::new(0.5, 2.1, 1.5)));
+  assert!(!a.contains(Pt3::new(0.5, 1.0, 3.1)));
+  assert!(a.contains_box(&aabb([0.25, 0.5, 1.0], [0.75, 1.5, 2.0])));
+  assert!(a.contains_box(&a));
+  assert!(!a.contains_box(&aabb([0.5, 0.5, 0.5], [1.5, 1.5, 1.5])));
+}
@@ -0,0 +1,155 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the Mt4 transforms.
+
+use csg_math::{Mt4, Pt3, Pt4};
+
+fn assert_near(a: Pt3, b: Pt3) {
+  assert!((a - b).len() < 1.0e-12, "{a} is not {b}");
+}
+
+#[test]
+fn determinant() {
+  assert_eq!(Mt4::identity().determinant(), 1.0);
+  assert_eq!(Mt4::scale_matrix(2.0, 3.0, 4.0).determinant(), 24.0);
+  assert_eq!(Mt4::translate_matrix(5.0, -6.0, 7.0).determinant(), 1.0);
+  assert!((Mt4::rot_vec(0.6, 0.0, 0.8, 33.0).determinant() - 1.0).abs() < 1.0e-12);
+  // the w row counts too
+  let m = Mt4::new(
+    Pt4::new(2.0, 0.0, 0.0, 1.0),
+    Pt4::new(0.0, 3.0, 0.0, 0.0),
+    Pt4::new(0.0, 0.0, 4.0, 0.0),
+    Pt4::new(1.0, 0.0, 0.0, 1.0),
+  );
+  assert_eq!(m.determinant(), 12.0);
+  let a = Mt4::scale_matrix(2.0, 1.0, 3.0) * Mt4::rot_x_matrix(20.0);
+  assert!(((a * m).determinant() - 6.0 * 12.0).abs() < 1.0e-12);
+  let singular = Mt4::scale_matrix(1.0, 0.0, 1.0);
+  assert_eq!(singular.determinant(), 0.0);
+}
+
+#[test]
+fn mirror_matrix() {
+  // the plane x + y = 1
+  let m = Mt4::mirror_matrix(Pt3::new(1.0, 1.0, 0.0), Pt3::new(1.0, 0.0, 0.0));
+  assert_near(
+    Pt3::new(0.0, 0.0, 5.0).transformed(&m),
+    Pt3::new(1.0, 1.0, 5.0),
+  );
+  assert_near(
+    Pt3::new(0.5, 0.5, -2.0).transformed(&m),
+    Pt3::new(0.5, 0.5, -2.0),
+  );
+  let p = Pt3::new(3.0, -4.0, 2.0);
+  assert_near(p.transformed(&m).transformed(&m), p);
+  assert!((m.determinant() + 1.0).abs() < 1.0e-12);
+}
+
+#[test]
+fn rot_axis_matrix() {
+  // a quarter turn around an upright axis through (1, 1, 0)
+  let m = Mt4::rot_axis_matrix(Pt3::new(0.0, 0.0, 2.0), Pt3::new(1.0, 1.0, 0.0), 90.0);
+  assert_near(
+    Pt3::new(2.0, 1.0, 3.0).transformed(&m),
+    Pt3::new(1.0, 2.0, 3.0),
+  );
+  assert_near(
+    Pt3::new(1.0, 1.0, 7.0).transformed(&m),
+    Pt3::new(1.0, 1.0, 7.0),
+  );
+  // a third of a turn around the diagonal swaps the axes around
+  let m = Mt4::rot_axis_matrix(Pt3::new(1.0, 1.0, 1.0), Pt3::new(0.0, 0.0, 0.0), 120.0);
+  assert_near(
+    Pt3::new(1.0, 0.0, 0.0).transformed(&m),
+    Pt3::new(0.0, 1.0, 0.0),
+  );
+  assert_near(
+    Pt3::new(0.0, 1.0, 0.0).transformed(&m),
+    Pt3::new(0.0, 0.0, 1.0),
+  );
+  // through the origin it matches the rotations around the coordinate axes
+  let m = Mt4::rot_axis_matrix(Pt3::new(1.0, 0.0, 0.0), Pt3::new(0.0, 0.0, 0.0), 25.0);
+  let p = Pt3::new(1.0, 2.0, 3.0);
+  assert_near(p.transformed(&m), p.transformed(&Mt4::rot_x_matrix(25.0)));
+}
+
+/// The columns are 1..=16 counted down each column.
+fn counting() -> Mt4 {
+  Mt4::new(
+    Pt4::new(1.0, 2.0, 3.0, 4.0),
+    Pt4::new(5.0, 6.0, 7.0, 8.0),
+    Pt4::new(9.0, 10.0, 11.0, 12.0),
+    Pt4::new(13.0, 14.0, 15.0, 16.0),
+  )
+}
+
+#[test]
+fn matrix_times_vector() {
+  // the sum of the columns weighted by the vector, w included
+  assert_eq!(
+    counting() * Pt4::new(1.0, 2.0, 3.0, 4.0),
+    Pt4::new(90.0, 100.0, 110.0, 120.0)
+  );
+  assert_eq!(
+    counting() * Pt4::new(0.0, 0.0, 0.0, 1.0),
+    Pt4::new(13.0, 14.0, 15.0, 16.0)
+  );
+  // a point moves, a direction doesn't
+  let m = Mt4::translate_matrix(1.0, 2.0, 3.0);
+  assert_eq!(
+    m * Pt4::new(4.0, 5.0, 6.0, 1.0),
+    Pt4::new(5.0, 7.0, 9.0, 1.0)
+  );
+  assert_eq!(
+    m * Pt4::new(4.0, 5.0, 6.0, 0.0),
+    Pt4::new(4.0, 5.0, 6.0, 0.0)
+  );
+  assert_eq!(m * Pt3::new(4.0, 5.0, 6.0), Pt3::new(4.0, 5.0, 6.0));
+}
+
+#[test]
+fn matrix_times_matrix() {
+  let b = Mt4::new(
+    Pt4::new(1.0, 0.0, 0.0, 1.0),
+    Pt4::new(0.0, 1.0, 2.0, 0.0),
+    Pt4::new(0.0, 0.0, 1.0, 0.0),
+    Pt4::new(2.0, 0.0, 0.0, 1.0),
+  );
+  let ab = counting() * b;
+  assert_eq!(ab.x, Pt4::new(14.0, 16.0, 18.0, 20.0));
+  assert_eq!(ab.y, Pt4::new(23.0, 26.0, 29.0, 32.0));
+  assert_eq!(ab.z, Pt4::new(9.0, 10.0, 11.0, 12.0));
+  assert_eq!(ab.w, Pt4::new(15.0, 18.0, 21.0, 24.0));
+  // each column of the product is the left matrix times that column
+  assert_eq!(ab.w, counting() * b.w);
+  // translations add up and the right matrix is applied first
+  let m = Mt4::translate_matrix(1.0, 2.0, 3.0) * Mt4::translate_matrix(4.0, 5.0, 6.0);
+  assert!(m == Mt4::translate_matrix(5.0, 7.0, 9.0));
+  let m = Mt4::translate_matrix(1.0, 0.0, 0.0) * Mt4::scale_matrix(2.0, 2.0, 2.0);
+  assert_eq!(
+    m * Pt4::new(1.0, 1.0, 1.0, 1.0),
+    Pt4::new(3.0, 2.0, 2.0, 1.0)
+  );
+  assert!(counting() * Mt4::identity() == counting());
+  assert!(Mt4::identity() * counting() == counting());
+}
@@ -0,0 +1,182 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checks of the Quat rotations.
+
+use csg_math::{Mt4, Pt3, Quat};
+
+fn assert_near(a: Pt3, b: Pt3) {
+  assert!((a - b).len() < 1.0e-12, "{a} is not {b}");
+}
+
+fn assert_matrix_near(a: &Mt4, b: &Mt4) {
+  for i in 0..16 {
+    assert!((a[i] - b[i]).abs() < 1.0e-12, "{a} is not {b}");
+  }
+}
+
+/// Whether two quaternions are the same rotation, q and -q are.
+fn assert_same_rotation(a: Quat, b: Quat) {
+  assert!((a.dot(b).abs() - 1.0).abs() < 1.0e-12, "{a} is not {b}");
+}
+
+fn points() -> [Pt3; 4] {
+  [
+    Pt3::new(1.0, 0.0, 0.0),
+    Pt3::new(0.0, 1.0, 0.0),
+    Pt3::new(0.0, 0.0, 1.0),
+    Pt3::new(3.0, -4.0, 2.5),
+  ]
+}
+
+#[test]
+fn axis_angle_matches_rot_vec() {
+  for (axis, degrees) in [
+    (Pt3::new(0.0, 0.0, 1.0), 90.0),
+    (Pt3::new(1.0, 2.0, 3.0), 33.0),
+    (Pt3::new(-2.0, 0.5, 1.0), 200.0),
+    (Pt3::new(0.0, 1.0, 0.0), -45.0),
+  ] {
+    let q = Quat::from_axis_angle(axis, degrees);
+    let a = axis.normalized();
+    let m = Mt4::rot_vec(a.x, a.y, a.z, degrees);
+    assert_matrix_near(&q.to_mt4(), &m);
+    for p in points() {
+      assert_near(q.rotate(p), p.transformed(&m));
+    }
+  }
+  // the axis and angle come back, with the angle in 0..=180
+  let (axis, degrees) = Quat::from_axis_angle(Pt3::new(0.0, 0.0, 2.0), 270.0).to_axis_angle();
+  assert_near(axis, Pt3::new(0.0, 0.0, -1.0));
+  assert!((degrees - 90.0).abs() < 1.0e-12);
+}
+
+#[test]
+fn euler_matches_the_composed_matrices() {
+  for (x, y, z) in [
+    (30.0, 0.0, 0.0),
+    (0.0, -70.0, 0.0),
+    (10.0, 20.0, 30.0),
+    (-120.0, 85.0, 200.0),
+  ] {
+    let m = Mt4::rot_z_matrix(z) * Mt4::rot_y_matrix(y) * Mt4::rot_x_matrix(x);
+    let q = Quat::from_euler(x, y, z);
+    assert_matrix_near(&q.to_mt4(), &m);
+    for p in points() {
+      assert_near(q * p, p.transformed(&m));
+    }
+  }
+}
+
+#[test]
+fn matrix_round_trip() {
+  let axis = Pt3::new(1.0, 2.0, 3.0);
+  // the trace goes from 3 down to -1 at half a turn, where the conversion switches to
+  // starting from the largest of x, y and z
+  for degrees in [
+    0.0, 45.0, 90.0, 119.0, 121.0, 170.0, 179.0, 179.999, 180.0, 180.001, 260.0,
+  ] {
+    let q = Quat::from_axis_angle(axis, degrees);
+    let back = Quat::from_mt4(&q.to_mt4());
+    assert_same_rotation(back, q);
+    assert!((back.len() - 1.0).abs() < 1.0e-12);
+    assert_matrix_near(&back.to_mt4(), &q.to_mt4());
+  }
+  // half a turn around each coordinate axis takes each branch
+  for axis in points() {
+    let q = Quat::from_axis_angle(axis, 180.0);
+    assert_same_rotation(Quat::from_mt4(&q.to_mt4()), q);
+    let m = Mt4::rot_axis_matrix(axis, Pt3::new(0.0, 0.0, 0.0), 180.0);
+    assert_same_rotation(Quat::from(m), q);
+  }
+  // a translation is left out
+  let m = Mt4::translate_matrix(5.0, 6.0, 7.0) * Mt4::rot_x_matrix(40.0);
+  assert_same_rotation(Quat::from_mt4(&m), Quat::from_euler(40.0, 0.0, 0.0));
+}
+
+#[test]
+fn two_vectors() {
+  let from = Pt3::new(1.0, 2.0, 2.0);
+  let to = Pt3::new(-3.0, 0.0, 4.0);
+  let q = Quat::from_two_vectors(from, to);
+  assert_near(q.rotate(from) / 3.0, to / 5.0);
+  // the smallest rotation turns around the axis perpendicular to both
+  let (axis, degrees) = q.to_axis_angle();
+  assert_near(axis, from.cross(to).normalized());
+  let expected = (from.dot(to) / 15.0).acos().to_degrees();
+  assert!((degrees - expected).abs() < 1.0e-9);
+  // parallel vectors don't turn
+  assert_same_rotation(Quat::from_two_vectors(from, from * 7.0), Quat::identity());
+  // opposite vectors turn half way around a perpendicular axis
+  for from in points() {
+    let q = Quat::from_two_vectors(from, -from * 2.0);
+    assert_near(q.rotate(from), -from);
+    let (axis, degrees) = q.to_axis_angle();
+    assert!(axis.dot(from).abs() < 1.0e-12);
+    assert!((degrees - 180.0).abs() < 1.0e-9);
+  }
+}
+
+#[test]
+fn slerp() {
+  let a = Quat::from_axis_angle(Pt3::new(1.0, 1.0, 0.0), 20.0);
+  let b = Quat::from_euler(10.0, 50.0, -30.0);
+  assert_same_rotation(a.slerp(b, 0.0), a);
+  assert_same_rotation(a.slerp(b, 1.0), b);
+  assert_same_rotation(a.slerp(-b, 1.0), b);
+  // the middle is half the turn from a to b
+  let z = Pt3::new(0.0, 0.0, 1.0);
+  let middle = Quat::identity().slerp(Quat::from_axis_angle(z, 90.0), 0.5);
+  assert_same_rotation(middle, Quat::from_axis_angle(z, 45.0));
+  let quarter = Quat::identity().slerp(Quat::from_axis_angle(z, 90.0), 0.25);
+  assert_same_rotation(quarter, Quat::from_axis_angle(z, 22.5));
+  // three quarters of a turn one way is the short quarter turn the other way
+  let middle = Quat::identity().slerp(Quat::from_axis_angle(z, 270.0), 0.5);
+  assert_same_rotation(middle, Quat::from_axis_angle(z, -45.0));
+  let x = Pt3::new(1.0, 0.0, 0.0);
+  let s = std::f64::consts::FRAC_1_SQRT_2;
+  assert_near(middle.rotate(x), Pt3::new(s, -s, 0.0));
+  // q and -q give the same path
+  assert_same_rotation(a.slerp(b, 0.3), a.slerp(-b, 0.3));
+  // nearly equal rotations don't divide by zero
+  let c = Quat::from_axis_angle(Pt3::new(1.0, 1.0, 0.0), 20.0 + 1.0e-7);
+  assert!((a.slerp(c, 0.5).len() - 1.0).abs() < 1.0e-12);
+}
+
+#[test]
+fn rotate_points() {
+  let z = Pt3::new(0.0, 0.0, 1.0);
+  let q = Quat::from_axis_angle(z, 90.0);
+  assert_near(q.rotate(Pt3::new(1.0, 0.0, 3.0)), Pt3::new(0.0, 1.0, 3.0));
+  assert_near(q * Pt3::new(0.0, 1.0, 0.0), Pt3::new(-1.0, 0.0, 0.0));
+  assert_near(
+    Quat::identity() * Pt3::new(3.0, -4.0, 2.5),
+    Pt3::new(3.0, -4.0, 2.5),
+  );
+  // a product turns by the right rotation first
+  let r = Quat::from_axis_angle(Pt3::new(1.0, 0.0, 0.0), 90.0);
+  for p in points() {
+    assert_near((q * r) * p, q * (r * p));
+    assert_near(q.conjugate() * (q * p), p);
+    assert!(((q * p).len() - p.len()).abs() < 1.0e-12);
+  }
+}
@@ -0,0 +1,38 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Feeds arbitrary bytes to the stl reader, run with `cargo fuzz run read_stl` from the
+//! root folder. Reading may fail but must not panic, and whatever it reads must come
+//! back the same from a binary round trip.
+
+#![no_main]
+
+use {csg::Mesh, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(mesh) = Mesh::read_stl(data) {
+    let mut binary = Vec::new();
+    mesh.write_stl_bin(&mut binary).unwrap();
+    let again = Mesh::read_stl(&binary[..]).unwrap();
+    assert_eq!(mesh.triangles.len(), again.triangles.len());
+  }
+});